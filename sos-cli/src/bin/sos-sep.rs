@@ -0,0 +1,131 @@
+//! Speaks the SOS engine protocol of `sos_engine::protocol` over stdin and stdout.
+//!
+//! Options, set with `setoption name <name> value <value>`:
+//!
+//! - `Strategy` is `search` (the default), `easy`, `medium`, `hard` or `mcts`. `search` is the
+//!   alpha-beta search of the hard bot bounded by the limits of `go`, the others are the built-in
+//!   bots and ignore the limits.
+//! - `Seed` seeds the random choices of every strategy, 0 by default.
+//! - `Hash` is the size of the transposition table of `search` in megabytes, 16 by default. The
+//!   table is kept between the moves of a game and cleared by `newgame`.
+
+use sos_engine::bot::Bot;
+use sos_engine::cell::CellValue;
+use sos_engine::difficulty::Difficulty;
+use sos_engine::game::Game;
+use sos_engine::mcts::{Mcts, MctsConfig};
+use sos_engine::protocol::{Command, GoLimits, Reply};
+use sos_engine::search::{Search, SearchLimits, SearchTable};
+use sos_engine::strategy::Strategy;
+use sos_engine::transposition::TranspositionTable;
+use std::cell::RefCell;
+use std::io::{self, BufRead};
+use std::rc::Rc;
+use std::time::Duration;
+
+const DEFAULT_MOVETIME: Duration = Duration::from_millis(1000);
+const DEFAULT_HASH_MB: usize = 16;
+
+#[derive(PartialEq, Clone, Copy)]
+enum Player {
+    Search,
+    Bot(Difficulty),
+    Mcts,
+}
+
+struct Engine {
+    game: Option<Game>,
+    player: Player,
+    seed: u64,
+    table: SearchTable,
+}
+
+fn main() {
+    let table = Rc::new(RefCell::new(TranspositionTable::with_megabytes(DEFAULT_HASH_MB)));
+    let mut engine = Engine { game: None, player: Player::Search, seed: 0, table };
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Command::parse(&line) {
+            Ok(Command::Quit) => break,
+            Ok(command) => engine.handle(command),
+            Err(error) => println!("{}", Reply::Error(error.to_string())),
+        }
+    }
+}
+
+impl Engine {
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::Sep => {
+                println!("{}", Reply::Id { key: "name".to_string(), value: "sos-sep".to_string() });
+                println!("{}", Reply::SepOk);
+            }
+            Command::IsReady => println!("{}", Reply::ReadyOk),
+            Command::SetOption { name, value } => {
+                if let Err(message) = self.set_option(&name, &value) {
+                    println!("{}", Reply::Error(message));
+                }
+            }
+            Command::NewGame { rows, cols, players, rules } => match Game::new(rows, cols, players, rules) {
+                Ok(game) => {
+                    self.table.borrow_mut().clear();
+                    self.game = Some(game);
+                }
+                Err(error) => println!("{}", Reply::Error(error.to_string())),
+            },
+            Command::Position(game) => self.game = Some(game),
+            Command::Go(limits) => {
+                let Some(game) = &self.game else {
+                    println!("{}", Reply::Error("no position".to_string()));
+                    return;
+                };
+                let best = self.best_move(game, limits).map(|(pos, value)| game.format_move(pos, value));
+                println!("{}", Reply::BestMove(best));
+            }
+            Command::Quit => {}
+        }
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name.to_lowercase().as_str() {
+            "strategy" => {
+                self.player = match value.to_lowercase().as_str() {
+                    "search" => Player::Search,
+                    "mcts" => Player::Mcts,
+                    level => Player::Bot(Difficulty::from_name(level).ok_or(format!("unknown strategy `{}`", value))?),
+                };
+            }
+            "seed" => self.seed = value.parse().map_err(|_| format!("invalid seed `{}`", value))?,
+            "hash" => {
+                let megabytes = value.parse().map_err(|_| format!("invalid hash size `{}`", value))?;
+                self.table = Rc::new(RefCell::new(TranspositionTable::with_megabytes(megabytes)));
+            }
+            _ => return Err(format!("unknown option `{}`", name)),
+        }
+        Ok(())
+    }
+
+    fn best_move(&self, game: &Game, limits: GoLimits) -> Option<(u32, CellValue)> {
+        match self.player {
+            Player::Bot(difficulty) => Bot::new(difficulty, self.seed).with_table(self.table.clone()).choose_move(game),
+            Player::Mcts => Mcts::new(MctsConfig::default(), self.seed).choose_move(game),
+            Player::Search => {
+                let unlimited = limits == GoLimits::default();
+                let empty = game.board().count_empty().min(u8::MAX as usize) as u8;
+                let limits = SearchLimits {
+                    depth: limits.depth.unwrap_or(empty).max(1),
+                    nodes: limits.nodes.unwrap_or(u64::MAX),
+                    time: if unlimited { Some(DEFAULT_MOVETIME) } else { limits.movetime },
+                };
+                let result = Search::new(limits, self.seed).with_table(self.table.clone()).best_move(game)?;
+                println!("{}", Reply::Info(format!("depth {} score {} nodes {}", result.depth, result.score, result.nodes)));
+                Some((result.pos, result.value))
+            }
+        }
+    }
+}
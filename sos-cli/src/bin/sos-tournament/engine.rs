@@ -0,0 +1,151 @@
+use sos_engine::cell::CellValue;
+use sos_engine::game::Game;
+use sos_engine::protocol::{Command, GoLimits, Reply};
+use sos_engine::strategy::Strategy;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+// Time an engine gets on top of the move time before it counts as hanging.
+const GRACE: Duration = Duration::from_secs(2);
+
+/// A bot in another process that speaks the SOS engine protocol.
+pub struct ExternalEngine {
+    command: String,
+    name: String,
+    movetime: Duration,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl ExternalEngine {
+    /// Starts `command`, split at whitespace into the program and its arguments, and waits for
+    /// the handshake.
+    pub fn start(command: &str, movetime: Duration) -> Result<Self, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("empty engine command")?;
+        let mut child = std::process::Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| format!("cannot start `{}`: {}", command, error))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // The replies are read on their own thread, so a hanging engine can be timed out.
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            command: command.to_string(),
+            name: command.to_string(),
+            movetime,
+            child,
+            stdin,
+            lines,
+        };
+        engine.send(&Command::Sep)?;
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            match engine.receive(deadline)? {
+                Reply::Id { key, value } if key == "name" => engine.name = value,
+                Reply::SepOk => break,
+                _ => {}
+            }
+        }
+        Ok(engine)
+    }
+
+    /// The name the engine gave in the handshake.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tells the engine a new game starts and seeds it. Engines without a `Seed` option may
+    /// answer with an error, which is ignored.
+    pub fn new_game(&mut self, game: &Game, seed: u64) -> Result<(), String> {
+        self.send(&Command::NewGame {
+            rows: game.get_rows(),
+            cols: game.get_cols(),
+            players: game.get_num_of_players(),
+            rules: game.get_rules(),
+        })?;
+        self.send(&Command::SetOption { name: "Seed".to_string(), value: seed.to_string() })?;
+        self.send(&Command::IsReady)?;
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while self.receive(deadline)? != Reply::ReadyOk {}
+        Ok(())
+    }
+
+    fn send(&mut self, command: &Command) -> Result<(), String> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|error| format!("{}: {}", self.command, error))
+    }
+
+    // The next reply of the engine, lines that are not part of the protocol are skipped.
+    fn receive(&self, deadline: Instant) -> Result<Reply, String> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(timeout) {
+                Ok(line) => {
+                    if let Ok(reply) = Reply::parse(&line) {
+                        return Ok(reply);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Err(format!("{}: no reply in time", self.name)),
+                Err(RecvTimeoutError::Disconnected) => return Err(format!("{}: the engine exited", self.name)),
+            }
+        }
+    }
+
+    fn best_move(&mut self, game: &Game) -> Result<(u32, CellValue), String> {
+        self.send(&Command::Position(game.clone()))?;
+        self.send(&Command::Go(GoLimits { movetime: Some(self.movetime), ..GoLimits::default() }))?;
+        let deadline = Instant::now() + self.movetime + GRACE;
+        loop {
+            match self.receive(deadline)? {
+                Reply::BestMove(Some(text)) => {
+                    return game.parse_move(&text).map_err(|error| format!("{}: {}", self.name, error));
+                }
+                Reply::BestMove(None) => return Err(format!("{}: no move", self.name)),
+                Reply::Error(message) => return Err(format!("{}: {}", self.name, message)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Strategy for ExternalEngine {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        match self.best_move(game) {
+            Ok(mv) => Some(mv),
+            Err(message) => {
+                eprintln!("{}", message);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        let _ = self.send(&Command::Quit);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,353 @@
+//! Plays two player matches between bots and rates them.
+//!
+//! ```text
+//! sos-tournament [--rows N] [--cols N] [--rules NAME] [--games N] [--seed N] [--opening N]
+//!                [--movetime MS] [--gauntlet] [--quiet] <bot> <bot>...
+//! ```
+//!
+//! A bot is `easy`, `medium` or `hard` for the built-in levels, `searchN` for the alpha-beta
+//! search to depth N, `mcts` or `mctsN` for MCTS with N iterations, or `engine:<command>` for a
+//! program speaking the SOS engine protocol, which gets `--movetime` per move.
+//!
+//! Every pair of bots plays `--games` games, or only the first bot against each of the others
+//! with `--gauntlet`. Games come in pairs that start from the same random opening of
+//! `--opening` moves with the bots swapping who moves first. The openings and the seeds of the
+//! bots follow from `--seed`, so a tournament can be run again with the same games.
+
+mod engine;
+mod stats;
+
+use engine::ExternalEngine;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sos_engine::bot::Bot;
+use sos_engine::cell::CellValue;
+use sos_engine::difficulty::Difficulty;
+use sos_engine::game::{Game, MAX_CELLS};
+use sos_engine::mcts::{Mcts, MctsConfig};
+use sos_engine::notation::{format_rules, parse_rules};
+use sos_engine::outcome::PlayerResult;
+use sos_engine::rules::Rules;
+use sos_engine::search::{Search, SearchLimits};
+use sos_engine::strategy::Strategy;
+use stats::{format_elo, Record};
+use std::process::ExitCode;
+use std::time::Duration;
+
+struct Options {
+    rows: u16,
+    cols: u16,
+    rules: Rules,
+    games: u32,
+    seed: u64,
+    opening: u32,
+    movetime: Duration,
+    gauntlet: bool,
+    quiet: bool,
+    bots: Vec<Spec>,
+}
+
+#[derive(Clone)]
+enum Spec {
+    Level(Difficulty),
+    Search(u8),
+    Mcts(u32),
+    Engine(String),
+}
+
+struct Participant {
+    name: String,
+    spec: Spec,
+    // Started once and kept running for all games of an external engine.
+    engine: Option<ExternalEngine>,
+    // Created fresh for every game of a built-in bot.
+    strategy: Option<Box<dyn Strategy>>,
+    record: Record,
+}
+
+impl Participant {
+    fn start_game(&mut self, game: &Game, seed: u64) -> Result<(), String> {
+        self.strategy = match self.spec {
+            Spec::Level(difficulty) => Some(Box::new(Bot::new(difficulty, seed))),
+            Spec::Search(depth) => Some(Box::new(SearchBot(Search::new(SearchLimits {
+                depth,
+                nodes: u64::MAX,
+                time: None,
+            }, seed)))),
+            Spec::Mcts(iterations) => Some(Box::new(Mcts::new(MctsConfig { iterations, ..MctsConfig::default() }, seed))),
+            Spec::Engine(_) => None,
+        };
+        match &mut self.engine {
+            Some(engine) => engine.new_game(game, seed),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Strategy for Participant {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        match (&mut self.engine, &mut self.strategy) {
+            (Some(engine), _) => engine.choose_move(game),
+            (None, Some(strategy)) => strategy.choose_move(game),
+            (None, None) => None,
+        }
+    }
+}
+
+// The search on its own, without the greedy fallback of `Bot`.
+struct SearchBot(Search);
+
+impl Strategy for SearchBot {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        self.0.best_move(game).map(|result| (result.pos, result.value))
+    }
+}
+
+/// The end of a game, counted for the bot that moved first.
+struct GameResult {
+    points: f64,
+    margin: i64,
+    // The bot that could not produce a legal move and lost the game because of it.
+    forfeit: Option<usize>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("sos-tournament: {}", error);
+            eprintln!("usage: sos-tournament [--rows N] [--cols N] [--rules NAME] [--games N] [--seed N] [--opening N] [--movetime MS] [--gauntlet] [--quiet] <bot> <bot>...");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut participants = vec![];
+    for spec in &options.bots {
+        let (name, engine) = match spec {
+            Spec::Level(difficulty) => (difficulty.name().to_lowercase(), None),
+            Spec::Search(depth) => (format!("search{}", depth), None),
+            Spec::Mcts(iterations) => (format!("mcts{}", iterations), None),
+            Spec::Engine(command) => match ExternalEngine::start(command, options.movetime) {
+                Ok(engine) => (engine.name().to_string(), Some(engine)),
+                Err(error) => {
+                    eprintln!("sos-tournament: {}", error);
+                    return ExitCode::FAILURE;
+                }
+            },
+        };
+        participants.push(Participant { name, spec: spec.clone(), engine, strategy: None, record: Record::default() });
+    }
+    // Tell apart bots with the same name, e.g. the same engine twice.
+    for index in 0..participants.len() {
+        if participants.iter().filter(|other| other.name == participants[index].name).count() > 1 {
+            participants[index].name = format!("{}#{}", participants[index].name, index + 1);
+        }
+    }
+
+    let pairings: Vec<(usize, usize)> = if options.gauntlet {
+        (1..participants.len()).map(|other| (0, other)).collect()
+    } else {
+        (0..participants.len())
+            .flat_map(|first| (first + 1..participants.len()).map(move |second| (first, second)))
+            .collect()
+    };
+
+    let mut results = vec![];
+    let mut number = 0;
+    for (pairing, &(a, b)) in pairings.iter().enumerate() {
+        let mut record = Record::default();
+        for round in 0..options.games {
+            number += 1;
+            // Both games of a pair start from the same opening, with the bots swapped.
+            let opening_seed = options.seed.wrapping_add(pairing as u64 * options.games as u64 + (round / 2) as u64);
+            let bot_seed = options.seed.wrapping_add(number as u64);
+            let (first, second) = if round & 1 == 0 { (a, b) } else { (b, a) };
+            let result = match play_game(&options, &mut participants, first, second, opening_seed, bot_seed) {
+                Ok(result) => result,
+                Err(error) => {
+                    eprintln!("sos-tournament: {}", error);
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            let (points, margin) = if first == a { (result.points, result.margin) } else { (1.0 - result.points, -result.margin) };
+            record.add(points, margin);
+            participants[a].record.add(points, margin);
+            participants[b].record.add(1.0 - points, -margin);
+            if !options.quiet {
+                let outcome = match (result.points, result.forfeit) {
+                    (_, Some(player)) => format!("{} forfeits", participants[player].name),
+                    (points, None) if points > 0.75 => format!("{} wins", participants[first].name),
+                    (points, None) if points < 0.25 => format!("{} wins", participants[second].name),
+                    _ => "draw".to_string(),
+                };
+                println!(
+                    "Game {}: {} - {}  {:+}  {}",
+                    number,
+                    participants[first].name,
+                    participants[second].name,
+                    result.margin,
+                    outcome,
+                );
+            }
+        }
+        results.push((a, b, record));
+    }
+
+    print_report(&options, &participants, &results);
+    ExitCode::SUCCESS
+}
+
+fn play_game(
+    options: &Options,
+    participants: &mut [Participant],
+    first: usize,
+    second: usize,
+    opening_seed: u64,
+    bot_seed: u64,
+) -> Result<GameResult, String> {
+    let mut game = Game::new(options.rows, options.cols, 2, options.rules).expect("the board size is checked with the options");
+    let mut rng = StdRng::seed_from_u64(opening_seed);
+    for _ in 0..options.opening {
+        let moves: Vec<(u32, CellValue)> = game.legal_moves().collect();
+        if moves.is_empty() {
+            break;
+        }
+        let (pos, value) = moves[rng.gen_range(0..moves.len())];
+        game.make_move(pos, value).map_err(|error| error.to_string())?;
+    }
+
+    let seats = [first, second];
+    for (player, &seat) in seats.iter().enumerate() {
+        participants[seat].start_game(&game, bot_seed.wrapping_add(player as u64))?;
+    }
+    while !game.is_game_over() {
+        let player = game.get_current_turn() as usize;
+        if game.play_strategy(&mut participants[seats[player]]).is_err() {
+            let scores = game.get_scores();
+            return Ok(GameResult {
+                points: player as f64,
+                margin: scores[0] as i64 - scores[1] as i64,
+                forfeit: Some(seats[player]),
+            });
+        }
+    }
+
+    let scores = game.get_scores();
+    let points = match game.outcome().map(|outcome| outcome.result_for(0)) {
+        Some(PlayerResult::Win) => 1.0,
+        Some(PlayerResult::Draw) => 0.5,
+        _ => 0.0,
+    };
+    Ok(GameResult { points, margin: scores[0] as i64 - scores[1] as i64, forfeit: None })
+}
+
+fn print_report(options: &Options, participants: &[Participant], results: &[(usize, usize, Record)]) {
+    let width = participants.iter().map(|participant| participant.name.len()).max().unwrap_or(0).max(3);
+    let games: u32 = results.iter().map(|(_, _, record)| record.games()).sum();
+
+    println!();
+    println!(
+        "{} games on {}x{} with {} rules, seed {}",
+        games, options.rows, options.cols, format_rules(options.rules), options.seed,
+    );
+    println!();
+    println!(
+        "{:>3}  {:<width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>6}  {:>13}  {:>7}",
+        "#", "Bot", "Games", "W", "D", "L", "Score", "Elo", "Margin", width = width,
+    );
+    let mut standings: Vec<&Participant> = participants.iter().collect();
+    standings.sort_by(|a, b| b.record.score().total_cmp(&a.record.score()));
+    for (rank, participant) in standings.iter().enumerate() {
+        let record = &participant.record;
+        println!(
+            "{:>3}  {:<width$}  {:>5}  {:>4}  {:>4}  {:>4}  {:>5.1}%  {:>13}  {:>+7.2}",
+            rank + 1,
+            participant.name,
+            record.games(),
+            record.wins,
+            record.draws,
+            record.losses,
+            100.0 * record.score(),
+            format_elo(record),
+            record.average_margin(),
+            width = width,
+        );
+    }
+
+    println!();
+    println!(
+        "{:<pair$}  {:>5}  {:>11}  {:>6}  {:>13}  {:>7}",
+        "Pairing", "Games", "W-D-L", "Score", "Elo", "Margin", pair = 2 * width + 4,
+    );
+    for (a, b, record) in results {
+        println!(
+            "{:<pair$}  {:>5}  {:>11}  {:>5.1}%  {:>13}  {:>+7.2}",
+            format!("{} vs {}", participants[*a].name, participants[*b].name),
+            record.games(),
+            format!("{}-{}-{}", record.wins, record.draws, record.losses),
+            100.0 * record.score(),
+            format_elo(record),
+            record.average_margin(),
+            pair = 2 * width + 4,
+        );
+    }
+    println!();
+    println!("Elo is against all opponents in the standings and for the first bot in the pairings, ± a 95% interval.");
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        rows: 6,
+        cols: 6,
+        rules: Rules::classic(),
+        games: 10,
+        seed: 0,
+        opening: 2,
+        movetime: Duration::from_millis(500),
+        gauntlet: false,
+        quiet: false,
+        bots: vec![],
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--rows" => options.rows = value()?.parse().map_err(|_| "invalid number of rows")?,
+            "--cols" => options.cols = value()?.parse().map_err(|_| "invalid number of columns")?,
+            "--rules" => options.rules = parse_rules(&value()?).map_err(|error| error.to_string())?,
+            "--games" => options.games = value()?.parse().map_err(|_| "invalid number of games")?,
+            "--seed" => options.seed = value()?.parse().map_err(|_| "invalid seed")?,
+            "--opening" => options.opening = value()?.parse().map_err(|_| "invalid number of opening moves")?,
+            "--movetime" => options.movetime = Duration::from_millis(value()?.parse().map_err(|_| "invalid move time")?),
+            "--gauntlet" => options.gauntlet = true,
+            "--quiet" => options.quiet = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown argument {}", arg)),
+            _ => options.bots.push(parse_spec(&arg)?),
+        }
+    }
+    if options.rows == 0 || options.cols == 0 || options.rows as u32 * options.cols as u32 > MAX_CELLS {
+        return Err("invalid board size".to_string());
+    }
+    if options.bots.len() < 2 {
+        return Err("at least two bots are needed".to_string());
+    }
+    Ok(options)
+}
+
+fn parse_spec(text: &str) -> Result<Spec, String> {
+    let invalid = || format!("unknown bot {}", text);
+    if let Some(command) = text.strip_prefix("engine:") {
+        return Ok(Spec::Engine(command.to_string()));
+    }
+    if let Some(difficulty) = Difficulty::from_name(text) {
+        return Ok(Spec::Level(difficulty));
+    }
+    if let Some(depth) = text.strip_prefix("search") {
+        return depth.parse().ok().filter(|&depth| depth > 0).map(Spec::Search).ok_or_else(invalid);
+    }
+    match text.strip_prefix("mcts") {
+        Some("") => Ok(Spec::Mcts(MctsConfig::default().iterations)),
+        Some(iterations) => iterations.parse().ok().filter(|&iterations| iterations > 0).map(Spec::Mcts).ok_or_else(invalid),
+        None => Err(invalid()),
+    }
+}
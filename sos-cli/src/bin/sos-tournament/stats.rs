@@ -0,0 +1,83 @@
+/// Results of a bot against its opponents, counted from the bot's point of view.
+#[derive(Clone, Copy, Default)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Sum of the bot's score minus the opponent's score over all games.
+    pub margin: i64,
+}
+
+impl Record {
+    /// Adds a game worth `points`, 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn add(&mut self, points: f64, margin: i64) {
+        if points > 0.75 {
+            self.wins += 1;
+        } else if points > 0.25 {
+            self.draws += 1;
+        } else {
+            self.losses += 1;
+        }
+        self.margin += margin;
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    /// The share of the points won, between 0 and 1.
+    pub fn score(&self) -> f64 {
+        self.points() / self.games().max(1) as f64
+    }
+
+    pub fn average_margin(&self) -> f64 {
+        self.margin as f64 / self.games().max(1) as f64
+    }
+
+    /// The Elo difference to the opponents and the half width of its 95% confidence interval.
+    /// The difference is `None` after only wins or only losses, the error when the interval
+    /// reaches past them.
+    pub fn elo(&self) -> (Option<f64>, Option<f64>) {
+        let games = self.games() as f64;
+        let score = self.score();
+        if games == 0.0 {
+            return (None, None);
+        }
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games;
+        let error = 1.96 * (variance / games).sqrt();
+        let spread = match (elo_difference(score - error), elo_difference(score + error)) {
+            (Some(low), Some(high)) => Some((high - low) / 2.0),
+            _ => None,
+        };
+        (elo_difference(score), spread)
+    }
+}
+
+/// The Elo difference that makes `score` the expected share of the points.
+fn elo_difference(score: f64) -> Option<f64> {
+    if score <= 0.0 || score >= 1.0 {
+        return None;
+    }
+    Some(400.0 * (score / (1.0 - score)).log10())
+}
+
+/// Writes an Elo estimate as `+120 ± 45`, with `inf` for the parts that are unbounded.
+pub fn format_elo(record: &Record) -> String {
+    let difference = match record.elo().0 {
+        Some(difference) => format!("{:+.0}", difference),
+        None if record.games() == 0 => "-".to_string(),
+        None if record.score() >= 1.0 => "+inf".to_string(),
+        None => "-inf".to_string(),
+    };
+    match record.elo().1 {
+        Some(error) => format!("{} ± {:.0}", difference, error),
+        None => format!("{} ± inf", difference),
+    }
+}
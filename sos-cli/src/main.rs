@@ -0,0 +1,225 @@
+//! Plays SOS in the terminal.
+//!
+//! ```text
+//! sos-cli [--rows N] [--cols N] [--rules classic|scoring|simple|simple+extra]
+//!         [--seats human,hard,...] [--seed N]
+//! ```
+//!
+//! Each seat is `human`, `easy`, `medium`, `hard` or `mcts`, the number of seats is the number of
+//! players. Moves are entered as a letter and a square, e.g. `S c4` or `S@c4`.
+
+use sos_engine::bot::Bot;
+use sos_engine::controller::{Controller, Seat};
+use sos_engine::difficulty::Difficulty;
+use sos_engine::game::{Game, MAX_CELLS};
+use sos_engine::mcts::{Mcts, MctsConfig};
+use sos_engine::notation::{format_column, parse_rules};
+use sos_engine::outcome::{Outcome, WinReason};
+use sos_engine::rules::Rules;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+const HELP: &str = "Enter a move as a letter and a square, e.g. `S c4` or `O@b2`.
+Other commands: `undo`, `help`, `quit`.";
+
+struct Options {
+    rows: u16,
+    cols: u16,
+    rules: Rules,
+    seats: Vec<String>,
+    seed: u64,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("sos-cli: {}", error);
+            eprintln!("usage: sos-cli [--rows N] [--cols N] [--rules NAME] [--seats human,hard,...] [--seed N]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut names = vec![];
+    let mut seats = vec![];
+    for (player, seat) in options.seats.iter().enumerate() {
+        let (name, seat) = match seat.as_str() {
+            "human" => (format!("Player {}", player + 1), Seat::Human),
+            "mcts" => (
+                format!("MCTS bot {}", player + 1),
+                Seat::Computer(Box::new(Mcts::new(MctsConfig::default(), options.seed.wrapping_add(player as u64)))),
+            ),
+            level => {
+                let difficulty = Difficulty::from_name(level).expect("seats are checked when parsing the arguments");
+                (format!("{} bot {}", difficulty.name(), player + 1), Seat::Computer(Box::new(Bot::new(difficulty, options.seed.wrapping_add(player as u64)))))
+            }
+        };
+        names.push(name);
+        seats.push(seat);
+    }
+
+    let game = Game::new(options.rows, options.cols, seats.len() as u8, options.rules).expect("the board size is checked with the options");
+    let mut controller = Controller::new(game, seats).expect("one seat per player");
+    println!("{}", HELP);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while !controller.game().is_game_over() {
+        println!();
+        print_board(controller.game());
+        print_scores(controller.game(), &names);
+        let player = controller.game().get_current_turn();
+        let name = &names[player as usize];
+
+        if controller.is_computer_turn() {
+            match controller.play_computer_turn() {
+                Ok((pos, value, response)) => println!("{} plays {}{}", name, controller.game().format_move(pos, value), sos_suffix(response.new_sos.len())),
+                Err(error) => {
+                    eprintln!("{} cannot move: {}", name, error);
+                    return ExitCode::FAILURE;
+                }
+            }
+            continue;
+        }
+
+        print!("{} to move> ", name);
+        let _ = io::stdout().flush();
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            return ExitCode::SUCCESS;
+        };
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => return ExitCode::SUCCESS,
+            "help" => println!("{}", HELP),
+            "undo" => undo(&mut controller),
+            text => {
+                let text = match text.split_whitespace().collect::<Vec<_>>()[..] {
+                    [letter, square] => format!("{}@{}", letter, square),
+                    _ => text.to_string(),
+                };
+                let result = controller.game().parse_move(&text)
+                    .map_err(|error| error.to_string())
+                    .and_then(|(pos, value)| controller.play(pos, value).map_err(|error| error.to_string()));
+                match result {
+                    Ok(response) if !response.new_sos.is_empty() => println!("{}{}", name, sos_suffix(response.new_sos.len()).replacen(" and", "", 1)),
+                    Ok(_) => {}
+                    Err(error) => println!("Invalid move: {}", error),
+                }
+            }
+        }
+    }
+
+    println!();
+    print_board(controller.game());
+    print_summary(controller.game(), &names);
+    ExitCode::SUCCESS
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        rows: 10,
+        cols: 10,
+        rules: Rules::classic(),
+        seats: vec!["human".to_string(), "easy".to_string()],
+        seed: 0,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--rows" => options.rows = value()?.parse().map_err(|_| "invalid number of rows")?,
+            "--cols" => options.cols = value()?.parse().map_err(|_| "invalid number of columns")?,
+            "--rules" => options.rules = parse_rules(&value()?).map_err(|error| error.to_string())?,
+            "--seats" => options.seats = value()?.split(',').map(|seat| seat.trim().to_lowercase()).collect(),
+            "--seed" => options.seed = value()?.parse().map_err(|_| "invalid seed")?,
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    if options.rows == 0 || options.cols == 0 || options.rows as u32 * options.cols as u32 > MAX_CELLS {
+        return Err("invalid board size".to_string());
+    }
+    if options.seats.is_empty() || options.seats.len() > u8::MAX as usize {
+        return Err("invalid number of seats".to_string());
+    }
+    for seat in &options.seats {
+        let known = seat == "human" || seat == "mcts" || Difficulty::from_name(seat).is_some();
+        if !known {
+            return Err(format!("unknown seat {}", seat));
+        }
+    }
+    Ok(options)
+}
+
+fn sos_suffix(sos: usize) -> String {
+    match sos {
+        0 => String::new(),
+        1 => " and completes an SOS".to_string(),
+        sos => format!(" and completes {} SOS", sos),
+    }
+}
+
+// Takes back the bots' replies together with the move of the person before them.
+fn undo(controller: &mut Controller) {
+    if !controller.game().can_undo() {
+        println!("Nothing to undo");
+        return;
+    }
+    while let Some(record) = controller.undo() {
+        if !controller.is_computer(record.player) {
+            break;
+        }
+    }
+}
+
+fn print_board(game: &Game) {
+    let board = game.board();
+    let label_width = board.get_rows().to_string().len();
+    let columns: Vec<String> = (0..board.get_cols()).map(format_column).collect();
+    let cell_width = columns.iter().map(|label| label.len()).max().unwrap_or(1);
+
+    print!("{:width$} ", "", width = label_width);
+    for label in &columns {
+        print!(" {:>width$}", label, width = cell_width);
+    }
+    println!();
+    for (row, cells) in board.rows().enumerate() {
+        print!("{:>width$} ", row + 1, width = label_width);
+        for cell in cells {
+            print!(" {:>width$}", cell.to_char(), width = cell_width);
+        }
+        println!();
+    }
+}
+
+fn print_scores(game: &Game, names: &[String]) {
+    let scores: Vec<String> = game.get_scores().iter()
+        .enumerate()
+        .map(|(player, score)| format!("{}: {}", names[player], score))
+        .collect();
+    println!("{}", scores.join("   "));
+}
+
+fn print_summary(game: &Game, names: &[String]) {
+    println!("Game over after {} moves.", game.get_history().len());
+    let mut standings: Vec<(usize, u32)> = game.get_scores().into_iter().enumerate().collect();
+    standings.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    for (player, score) in standings {
+        println!("  {:<20} {}", names[player], score);
+    }
+    match game.outcome() {
+        Some(Outcome::Win { player, reason: WinReason::FirstSos }) => {
+            println!("{} wins with the first SOS.", names[player as usize]);
+        }
+        Some(Outcome::Win { player, .. }) => println!("{} wins.", names[player as usize]),
+        Some(Outcome::Draw { players, .. }) => {
+            let players: Vec<&str> = players.iter().map(|&player| names[player as usize].as_str()).collect();
+            println!("Draw between {}.", players.join(" and "));
+        }
+        None => {}
+    }
+    println!("Final position: {}", game.to_notation());
+    let empty = game.board().count_empty();
+    if empty > 0 {
+        println!("{} cells were left empty.", empty);
+    }
+}
@@ -0,0 +1,86 @@
+//! Counting the SOS of every move with the bitsets against listing them, the bots built on top
+//! and the endgame solver. Run with `cargo bench -p sos-engine`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sos_engine::search::{Search, SearchLimits};
+use sos_engine::solver::{Solver, SolverLimits};
+use sos_engine::{Bot, CellValue, Difficulty, Game, Rules, Strategy};
+
+/// A board of `size` by `size` with half of the cells filled at random.
+fn half_full(size: u16) -> Game {
+    filled(size, size as u32 * size as u32 / 2)
+}
+
+/// A board of `size` by `size` filled at random until `empty` cells are left.
+fn filled(size: u16, empty: u32) -> Game {
+    let mut game = Game::new(size, size, 2, Rules::default()).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in empty..game.board().get_num_of_cells() {
+        let moves: Vec<(u32, CellValue)> = game.legal_moves().collect();
+        let (pos, value) = moves[rng.gen_range(0..moves.len())];
+        game.make_move(pos, value).unwrap();
+    }
+    game
+}
+
+fn count_sos(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count_sos");
+    for size in [15, 100] {
+        let game = half_full(size);
+        let empty: Vec<u32> = game.board().empty_cells().collect();
+        group.bench_with_input(BenchmarkId::new("list", size), &empty, |b, empty| {
+            b.iter(|| {
+                let mut total = 0;
+                for &pos in empty {
+                    total += game.get_sos_for_move(pos, CellValue::S).len();
+                    total += game.get_sos_for_move(pos, CellValue::O).len();
+                }
+                black_box(total)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bitboard", size), &empty, |b, empty| {
+            b.iter(|| {
+                let mut total = 0;
+                for &pos in empty {
+                    total += game.count_sos_for_move(pos, CellValue::S);
+                    total += game.count_sos_for_move(pos, CellValue::O);
+                }
+                black_box(total)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bots(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bots");
+    group.sample_size(10);
+    for size in [15, 30] {
+        let game = half_full(size);
+        group.bench_with_input(BenchmarkId::new("easy", size), &game, |b, game| {
+            b.iter(|| Bot::new(Difficulty::Easy, 1).choose_move(game))
+        });
+        let limits = SearchLimits { depth: 2, nodes: u64::MAX, time: None };
+        group.bench_with_input(BenchmarkId::new("search depth 2", size), &game, |b, game| {
+            b.iter(|| Search::new(limits, 1).best_move(game))
+        });
+    }
+    group.finish();
+}
+
+fn solver(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solver");
+    group.sample_size(10);
+    for empty in [8, 10, 12] {
+        let game = filled(10, empty);
+        group.bench_with_input(BenchmarkId::new("10x10", empty), &game, |b, game| {
+            b.iter(|| Solver::new(SolverLimits { cells: empty, nodes: u64::MAX }).solve(game))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, count_sos, bots, solver);
+criterion_main!(benches);
@@ -0,0 +1,226 @@
+//! The board as two bitsets, one for the S cells and one for the O cells.
+//!
+//! Bots ask how many SOS a letter would complete for every empty cell, many times per move.
+//! `Game::add_s` and `Game::add_o` build the SOS themselves, while a `BitBoard` only counts
+//! them with a few bit tests and never allocates. Whether a line of three fits on the board is
+//! precomputed per row and per column as masks of the directions, so nothing wraps around to the
+//! next row.
+
+use crate::board::{Board, NEIGHBOURS};
+use crate::cell::CellValue;
+use crate::threat::Threat;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct BitBoard {
+    cols: u16,
+    s: Vec<u64>,
+    o: Vec<u64>,
+    // Bit `i` is set when one step towards `NEIGHBOURS[i]` stays in the row or column, bit
+    // `8 + i` when two steps do. A cell's lines are the mask of its row and its column combined.
+    row_lines: Vec<u16>,
+    col_lines: Vec<u16>,
+}
+
+impl BitBoard {
+    /// An empty board of `rows` by `cols` cells.
+    pub fn new(rows: u16, cols: u16) -> Self {
+        let words = (rows as usize * cols as usize).div_ceil(64);
+        Self {
+            cols,
+            s: vec![0; words],
+            o: vec![0; words],
+            row_lines: BitBoard::lines(rows, |(dr, _)| dr),
+            col_lines: BitBoard::lines(cols, |(_, dc)| dc),
+        }
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        let mut bits = BitBoard::new(board.get_rows(), board.get_cols());
+        for (pos, &value) in board.cells().iter().enumerate() {
+            bits.set(pos as u32, value);
+        }
+        bits
+    }
+
+    // The direction masks along one axis of `size` cells, `axis` picks the step along it.
+    fn lines(size: u16, axis: fn((i32, i32)) -> i32) -> Vec<u16> {
+        (0..size as i32)
+            .map(|index| {
+                let mut mask = 0;
+                for (i, &step) in NEIGHBOURS.iter().enumerate() {
+                    let fits = |steps: i32| (0..size as i32).contains(&(index + axis(step) * steps));
+                    mask |= (fits(1) as u16) << i | (fits(2) as u16) << (8 + i);
+                }
+                mask
+            })
+            .collect()
+    }
+
+    pub fn get(&self, pos: u32) -> CellValue {
+        let (word, bit) = (pos as usize / 64, 1 << (pos % 64));
+        if self.s[word] & bit != 0 {
+            CellValue::S
+        } else if self.o[word] & bit != 0 {
+            CellValue::O
+        } else {
+            CellValue::Empty
+        }
+    }
+
+    pub fn set(&mut self, pos: u32, value: CellValue) {
+        let (word, bit) = (pos as usize / 64, 1 << (pos % 64));
+        self.s[word] &= !bit;
+        self.o[word] &= !bit;
+        match value {
+            CellValue::S => self.s[word] |= bit,
+            CellValue::O => self.o[word] |= bit,
+            CellValue::Empty => {}
+        }
+    }
+
+    /// How many SOS `value` at `pos` would complete, the same number `Game::get_sos_for_move`
+    /// lists.
+    pub fn count_sos(&self, pos: u32, value: CellValue) -> usize {
+        match value {
+            CellValue::S => self.count_s(pos),
+            CellValue::O => self.count_o(pos),
+            CellValue::Empty => 0,
+        }
+    }
+
+    /// Whether `value` at `pos` would leave an SOS with only one cell missing, for the next
+    /// player to complete.
+    pub fn opens_sos(&self, pos: u32, value: CellValue) -> bool {
+        let lines = self.lines_at(pos);
+        match value {
+            // The S is one end, the O and the other S are the next two cells.
+            CellValue::S => NEIGHBOURS.iter().enumerate().any(|(i, &step)| {
+                let step = self.offset(step);
+                let (middle, end) = (pos as i64 + step, pos as i64 + 2 * step);
+                lines & 1 << (8 + i) != 0
+                    && (self.is_o(middle) && self.is_empty(end) || self.is_empty(middle) && self.is_s(end))
+            }),
+            // The O is in the middle with an S on one side.
+            CellValue::O => NEIGHBOURS[..4].iter().enumerate().any(|(i, &step)| {
+                let step = self.offset(step);
+                let (before, after) = (pos as i64 + step, pos as i64 - step);
+                lines & 1 << i != 0 && lines & 1 << (7 - i) != 0
+                    && (self.is_s(before) && self.is_empty(after) || self.is_empty(before) && self.is_s(after))
+            }),
+            CellValue::Empty => false,
+        }
+    }
+
+    /// The threats of the cell at `pos`, none when it is occupied.
+    pub fn threat(&self, pos: u32) -> Threat {
+        if !self.is_empty(pos as i64) {
+            return Threat::default();
+        }
+        Threat {
+            s: self.count_s(pos) as u8,
+            o: self.count_o(pos) as u8,
+            s_dangerous: self.opens_sos(pos, CellValue::S),
+            o_dangerous: self.opens_sos(pos, CellValue::O),
+        }
+    }
+
+    fn count_s(&self, pos: u32) -> usize {
+        let lines = self.lines_at(pos);
+        let mut count = 0;
+        for (i, &step) in NEIGHBOURS.iter().enumerate() {
+            if lines & 1 << (8 + i) != 0 {
+                let step = self.offset(step);
+                count += (self.is_o(pos as i64 + step) && self.is_s(pos as i64 + 2 * step)) as usize;
+            }
+        }
+        count
+    }
+
+    fn count_o(&self, pos: u32) -> usize {
+        let lines = self.lines_at(pos);
+        let mut count = 0;
+        // Each line through the cell once, as the step and its opposite at `7 - i`.
+        for (i, &step) in NEIGHBOURS[..4].iter().enumerate() {
+            if lines & 1 << i != 0 && lines & 1 << (7 - i) != 0 {
+                let step = self.offset(step);
+                count += (self.is_s(pos as i64 + step) && self.is_s(pos as i64 - step)) as usize;
+            }
+        }
+        count
+    }
+
+    fn lines_at(&self, pos: u32) -> u16 {
+        let (row, col) = (pos / self.cols as u32, pos % self.cols as u32);
+        self.row_lines[row as usize] & self.col_lines[col as usize]
+    }
+
+    // The step in positions. The line masks keep the cells it reaches on the board.
+    fn offset(&self, (dr, dc): (i32, i32)) -> i64 {
+        dr as i64 * self.cols as i64 + dc as i64
+    }
+
+    fn is_s(&self, pos: i64) -> bool {
+        self.s[pos as usize / 64] & 1 << (pos as usize % 64) != 0
+    }
+
+    fn is_o(&self, pos: i64) -> bool {
+        self.o[pos as usize / 64] & 1 << (pos as usize % 64) != 0
+    }
+
+    fn is_empty(&self, pos: i64) -> bool {
+        (self.s[pos as usize / 64] | self.o[pos as usize / 64]) & 1 << (pos as usize % 64) == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(rows: u16, cols: u16, letters: &[(u32, CellValue)]) -> BitBoard {
+        let mut bits = BitBoard::new(rows, cols);
+        for &(pos, value) in letters {
+            bits.set(pos, value);
+        }
+        bits
+    }
+
+    #[test]
+    fn lines_cross_word_boundaries() {
+        // 63 and 64 are the last bit of the first word and the first bit of the second.
+        let bits = board(10, 10, &[(62, CellValue::S), (63, CellValue::O)]);
+        assert_eq!(bits.count_sos(64, CellValue::S), 1);
+        let bits = board(10, 10, &[(54, CellValue::S), (74, CellValue::S)]);
+        assert_eq!(bits.count_sos(64, CellValue::O), 1);
+        assert!(board(10, 10, &[(63, CellValue::O)]).opens_sos(64, CellValue::S));
+    }
+
+    #[test]
+    fn lines_do_not_wrap_at_a_word_boundary() {
+        // With 8 columns, 63 ends row 8 and 64 starts row 9.
+        let bits = board(9, 8, &[(62, CellValue::S), (63, CellValue::O)]);
+        assert_eq!(bits.count_sos(64, CellValue::S), 0);
+        assert!(!bits.opens_sos(64, CellValue::S));
+        assert_eq!(bits.threat(64), Threat::default());
+    }
+
+    #[test]
+    fn one_column_boards_only_have_vertical_lines() {
+        let bits = board(5, 1, &[(1, CellValue::S), (3, CellValue::S)]);
+        assert_eq!(bits.count_sos(2, CellValue::O), 1);
+        assert_eq!(bits.count_sos(0, CellValue::S), 0);
+        assert_eq!(bits.threat(4), Threat::default());
+        let bits = board(5, 1, &[(0, CellValue::S), (1, CellValue::O)]);
+        assert_eq!(bits.threat(2), Threat { s: 1, ..Threat::default() });
+        assert!(board(5, 1, &[(0, CellValue::S)]).opens_sos(2, CellValue::S));
+    }
+
+    #[test]
+    fn set_replaces_the_letter() {
+        let mut bits = BitBoard::new(9, 9);
+        bits.set(80, CellValue::S);
+        bits.set(80, CellValue::O);
+        assert_eq!(bits.get(80), CellValue::O);
+        bits.set(80, CellValue::Empty);
+        assert_eq!(bits, BitBoard::new(9, 9));
+    }
+}
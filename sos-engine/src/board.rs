@@ -0,0 +1,91 @@
+use crate::cell::CellValue;
+
+/// One step to each of the eight neighbours of a cell, as rows and columns in reading order.
+/// The step at index `i` is the opposite of the one at `7 - i`.
+pub(crate) const NEIGHBOURS: [(i32, i32); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A read-only view of the cells of a game, returned by `Game::board`.
+///
+/// Positions count row by row from the top-left cell, so the cell in `row` and `col` is at
+/// `row * cols + col`.
+#[derive(Clone, Copy, Debug)]
+pub struct Board<'a> {
+    rows: u16,
+    cols: u16,
+    cells: &'a [CellValue],
+}
+
+impl<'a> Board<'a> {
+    pub(crate) fn new(rows: u16, cols: u16, cells: &'a [CellValue]) -> Self {
+        Self { rows, cols, cells }
+    }
+
+    pub fn get_rows(&self) -> u16 {
+        self.rows
+    }
+
+    pub fn get_cols(&self) -> u16 {
+        self.cols
+    }
+
+    pub fn get_num_of_cells(&self) -> u32 {
+        self.cells.len() as u32
+    }
+
+    /// The cell at `pos`, or `None` outside the board.
+    pub fn get(&self, pos: u32) -> Option<CellValue> {
+        self.cells.get(pos as usize).copied()
+    }
+
+    /// The cell in `row` and `col`, counting from 0, or `None` outside the board.
+    pub fn get_at(&self, row: u16, col: u16) -> Option<CellValue> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        self.get(row as u32 * self.cols as u32 + col as u32)
+    }
+
+    pub fn is_empty_at(&self, pos: u32) -> bool {
+        self.get(pos) == Some(CellValue::Empty)
+    }
+
+    /// The row and the column of `pos`.
+    pub fn coordinates(&self, pos: u32) -> (u16, u16) {
+        ((pos / self.cols as u32) as u16, (pos % self.cols as u32) as u16)
+    }
+
+    /// The cell `steps` times `(rows, cols)` away from `pos`, or `None` when that is outside
+    /// the board. Steps never wrap around to another row.
+    pub fn neighbour(&self, pos: u32, (rows, cols): (i32, i32), steps: i32) -> Option<u32> {
+        if pos >= self.get_num_of_cells() {
+            return None;
+        }
+        let (row, col) = self.coordinates(pos);
+        let row = row as i64 + rows as i64 * steps as i64;
+        let col = col as i64 + cols as i64 * steps as i64;
+        if row < 0 || col < 0 || row >= self.rows as i64 || col >= self.cols as i64 {
+            return None;
+        }
+        Some((row * self.cols as i64 + col) as u32)
+    }
+
+    /// Every cell, row by row.
+    pub fn cells(&self) -> &'a [CellValue] {
+        self.cells
+    }
+
+    /// The cells of each row, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [CellValue]> {
+        self.cells.chunks(self.cols as usize)
+    }
+
+    /// The positions of the empty cells, in order.
+    pub fn empty_cells(&self) -> impl Iterator<Item = u32> + 'a {
+        let cells = self.cells;
+        (0..cells.len() as u32).filter(move |&pos| cells[pos as usize] == CellValue::Empty)
+    }
+
+    pub fn count_empty(&self) -> usize {
+        self.cells.iter().filter(|&&cell| cell == CellValue::Empty).count()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::cell::CellValue;
+use crate::difficulty::Difficulty;
+use crate::game::Game;
+use crate::search::{Search, SearchTable};
+use crate::solver::Solver;
+use crate::strategy::{move_rng, Strategy};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A bot of one difficulty, meant to play a single game.
+///
+/// The search and solver tables are kept from move to move, so what was searched for one move
+/// speeds up the next. The moves then also depend on the earlier ones, a seeded game is replayed
+/// move for move when it is played again from the start.
+pub struct Bot {
+    difficulty: Difficulty,
+    seed: u64,
+    // Created for the first endgame and kept, with its table, for the rest of the game.
+    solver: Option<Solver>,
+    // `None` for the levels that do not search.
+    search: Option<Search>,
+}
+
+impl Strategy for Bot {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        if game.is_game_over() {
+            return None;
+        }
+        if let Some(limits) = self.difficulty.solver_limits().filter(|limits| game.board().count_empty() as u32 <= limits.cells) {
+            let solver = self.solver.get_or_insert_with(|| Solver::new(limits));
+            if let Some(solution) = solver.solve(game) {
+                return solution.moves.choose(&mut move_rng(self.seed, game)).copied();
+            }
+        }
+        if let Some(result) = self.search.as_mut().and_then(|search| search.best_move(game)) {
+            return Some((result.pos, result.value));
+        }
+        Some(Bot::greedy_move(game, &mut move_rng(self.seed, game)))
+    }
+}
+
+impl Bot {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        let search = difficulty.search_limits().map(|limits| Search::new(limits, seed));
+        Self { difficulty, seed, solver: None, search }
+    }
+
+    /// Searches with `table` instead of a table of its own, e.g. one shared by the bots of an
+    /// engine, see `Search::with_table`.
+    pub fn with_table(self, table: SearchTable) -> Self {
+        Self { search: self.search.map(|search| search.with_table(table)), ..self }
+    }
+
+    fn greedy_move(game: &Game, rng: &mut impl Rng) -> (u32, CellValue) {
+        let mut candidates: Vec<(u32, CellValue)> = vec![];
+        let mut highest = 0;
+        for (i, threat) in game.hot_cells() {
+            for value in [CellValue::S, CellValue::O] {
+                let len = threat.count(value);
+                if len > highest {
+                    highest = len;
+                    candidates = vec![(i, value)];
+                } else if len == highest {
+                    candidates.push((i,value));
+                }
+            }
+        }
+
+        if highest == 0 {
+            let moves: Vec<(u32, CellValue)> = game.legal_moves().collect();
+            candidates = Bot::safe_moves(&moves, game);
+        }
+
+        candidates[rng.gen_range(0..candidates.len())]
+    }
+
+    // Never leave the next player a cell that completes an SOS, unless every move does. In a simple
+    // game that SOS would win it.
+    fn safe_moves(moves: &[(u32, CellValue)], game: &Game) -> Vec<(u32, CellValue)> {
+        let candidates: Vec<(u32, CellValue)> = moves.iter()
+            .copied()
+            .filter(|&(pos, val)| !game.get_threat(pos).is_dangerous(val))
+            .collect();
+        if candidates.is_empty() {
+            return moves.to_vec();
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::transposition::TranspositionTable;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn choose(difficulty: Difficulty, notation: &str, seed: u64) -> (u32, CellValue) {
+        let game = Game::from_notation(notation).expect("valid position");
+        Bot::new(difficulty, seed).choose_move(&game).expect("a move")
+    }
+
+    #[test]
+    fn every_level_completes_an_open_sos() {
+        for difficulty in Difficulty::ALL {
+            for seed in 0..5 {
+                assert_eq!(choose(difficulty, "4x4 SO2/4/4/4 1 0,0", seed), (2, CellValue::S));
+                assert_eq!(choose(difficulty, "4x4 4/S1S1/4/4 2 0,0", seed), (5, CellValue::O));
+            }
+        }
+    }
+
+    #[test]
+    fn easy_bot_takes_the_move_with_the_most_sos() {
+        // a1 and c3 complete one SOS each, b2 completes two.
+        for seed in 0..5 {
+            assert_eq!(choose(Difficulty::Easy, "3x4 1OS1/S1S1/SO2 1 0,0", seed), (5, CellValue::O));
+        }
+    }
+
+    #[test]
+    fn easy_bot_does_not_hand_over_an_sos() {
+        for notation in ["1x4 S3 1 0,0 classic", "3x3 S2/3/2S 2 0,0", "1x4 S3 1 0,0 simple"] {
+            let game = Game::from_notation(notation).unwrap();
+            for seed in 0..10 {
+                let (pos, value) = choose(Difficulty::Easy, notation, seed);
+                assert!(!game.get_threat(pos).is_dangerous(value), "{} with seed {}", notation, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn bot_has_no_move_when_the_game_is_over() {
+        let game = Game::from_notation("1x3 SOS 1 1,0").unwrap();
+        assert_eq!(Bot::new(Difficulty::Hard, 0).choose_move(&game), None);
+    }
+
+    #[test]
+    fn solver_takes_over_in_the_endgame() {
+        // Only the S at c1 keeps the draw, which a depth 2 search does not see.
+        let game = Game::from_notation("4x3 S2/2S/1S1/S2 1 0,0 classic").unwrap();
+        for difficulty in [Difficulty::Medium, Difficulty::Hard] {
+            for seed in 0..5 {
+                let mut bot = Bot::new(difficulty, seed);
+                assert_eq!(bot.choose_move(&game), Some((2, CellValue::S)));
+                assert!(bot.solver.is_some());
+            }
+        }
+        // Above the limit the bot searches as before.
+        let mut bot = Bot::new(Difficulty::Hard, 0);
+        bot.choose_move(&Game::new(5, 5, 2, Rules::classic()).unwrap());
+        assert!(bot.solver.is_none());
+    }
+
+    #[test]
+    fn keeps_the_search_table_between_moves() {
+        let table: SearchTable = Rc::new(RefCell::new(TranspositionTable::new(1 << 12)));
+        let mut bot = Bot::new(Difficulty::Medium, 3).with_table(table.clone());
+        let mut game = Game::new(4, 4, 2, Rules::classic()).unwrap();
+        let (pos, value) = bot.choose_move(&game).unwrap();
+        let stored = table.borrow().len();
+        assert!(stored > 0);
+        game.make_move(pos, value).unwrap();
+        bot.choose_move(&game).unwrap();
+        assert!(table.borrow().len() > stored);
+        assert!(Bot::new(Difficulty::Easy, 3).with_table(table).search.is_none());
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_move() {
+        let game = Game::new(5, 5, 2, Rules::classic()).unwrap();
+        for difficulty in Difficulty::ALL {
+            let first = Bot::new(difficulty, 11).choose_move(&game);
+            assert_eq!(Bot::new(difficulty, 11).choose_move(&game), first);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CellValue {
+    Empty,
+    S,
+    O
+}
+
+impl CellValue {
+    /// `S`, `O`, or `.` for an empty cell.
+    pub fn to_char(self) -> char {
+        match self {
+            CellValue::Empty => '.',
+            CellValue::S => 'S',
+            CellValue::O => 'O',
+        }
+    }
+
+    /// Reads a letter written by `to_char`, in either case.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            '.' => Some(CellValue::Empty),
+            'S' => Some(CellValue::S),
+            'O' => Some(CellValue::O),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::cell::CellValue;
+use crate::game::{Game, GameError, UpdateResponse};
+use crate::history::MoveRecord;
+use crate::strategy::Strategy;
+
+pub enum Seat {
+    /// Moves are entered by a person through `Controller::play`.
+    Human,
+    /// Moves are picked by the strategy through `Controller::play_computer_turn`.
+    Computer(Box<dyn Strategy>),
+}
+
+/// A game together with who plays each seat.
+pub struct Controller {
+    game: Game,
+    seats: Vec<Seat>,
+}
+
+impl Controller {
+    pub fn new(game: Game, seats: Vec<Seat>) -> Result<Self, GameError> {
+        if seats.len() != game.get_num_of_players() as usize {
+            return Err(GameError::InvalidPlayer);
+        }
+        Ok(Self { game, seats })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn set_seat(&mut self, player: u8, seat: Seat) -> Result<(), GameError> {
+        let slot = self.seats.get_mut(player as usize).ok_or(GameError::InvalidPlayer)?;
+        *slot = seat;
+        Ok(())
+    }
+
+    pub fn is_computer(&self, player: u8) -> bool {
+        matches!(self.seats.get(player as usize), Some(Seat::Computer(_)))
+    }
+
+    pub fn is_computer_turn(&self) -> bool {
+        !self.game.is_game_over() && self.is_computer(self.game.get_current_turn())
+    }
+
+    /// Plays a move for the human seat to move.
+    pub fn play(&mut self, pos: u32, value: CellValue) -> Result<UpdateResponse, GameError> {
+        let player = self.game.get_current_turn();
+        if self.is_computer(player) {
+            return Err(GameError::InvalidPlayer);
+        }
+        self.game.update(player, pos, value)
+    }
+
+    /// Lets the strategy of the computer seat to move play its move.
+    pub fn play_computer_turn(&mut self) -> Result<(u32, CellValue, UpdateResponse), GameError> {
+        let player = self.game.get_current_turn();
+        let strategy = match self.seats.get_mut(player as usize) {
+            Some(Seat::Computer(strategy)) => strategy,
+            _ => return Err(GameError::InvalidPlayer),
+        };
+        self.game.play_strategy(strategy.as_mut())
+    }
+
+    pub fn undo(&mut self) -> Option<MoveRecord> {
+        self.game.undo()
+    }
+
+    pub fn redo(&mut self) -> Option<MoveRecord> {
+        self.game.redo()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::search::SearchLimits;
+use crate::solver::SolverLimits;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    /// Takes the biggest immediate SOS and otherwise plays a random move that does not hand the
+    /// next player an SOS, if there is one.
+    #[default]
+    Easy,
+    /// Looks a couple of moves ahead with alpha-beta search.
+    Medium,
+    /// Searches deeper with a larger budget.
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Reads a name written by `name`, in any case.
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL.into_iter().find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    /// When this level solves the endgame exactly with `Solver`, `None` for the greedy level.
+    /// Every two more cells make solving about six times slower, and an endgame needing more
+    /// nodes is left to the search.
+    pub fn solver_limits(&self) -> Option<SolverLimits> {
+        match self {
+            Difficulty::Easy => None,
+            Difficulty::Medium => Some(SolverLimits { cells: 8, nodes: 50_000 }),
+            Difficulty::Hard => Some(SolverLimits { cells: 12, nodes: 300_000 }),
+        }
+    }
+
+    /// The search budget for this level, `None` for the greedy level that does not search. It is
+    /// bounded by nodes rather than time, so a seeded bot plays the same moves on any machine.
+    pub fn search_limits(&self) -> Option<SearchLimits> {
+        match self {
+            Difficulty::Easy => None,
+            Difficulty::Medium => Some(SearchLimits { depth: 2, nodes: 5_000, time: None }),
+            Difficulty::Hard => Some(SearchLimits { depth: 4, nodes: 60_000, time: None }),
+        }
+    }
+}
@@ -0,0 +1,662 @@
+use crate::bitboard::BitBoard;
+use crate::board::{Board, NEIGHBOURS};
+use crate::cell::CellValue;
+use crate::history::MoveRecord;
+use crate::outcome::{DrawReason, Outcome, WinReason};
+use crate::rules::{GameMode, Rules};
+use crate::strategy::Strategy;
+use crate::threat::Threat;
+use crate::zobrist;
+use std::collections::BTreeSet;
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct  UpdateResponse {
+    pub new_sos: Vec<(u32, u32, u32)>,
+    pub scores: Vec<u32>,
+    pub next_turn: u8
+}
+#[derive(Debug)]
+pub enum GameError {
+    InvalidPlayer,
+    InvalidPosition,
+    PositionAlreadyOccupied,
+    InvalidMove,
+    GameFinished,
+    /// The board has no cells or more than `MAX_CELLS`.
+    InvalidBoardSize,
+}
+
+/// The most cells a board can have, e.g. 4096 by 4096.
+pub const MAX_CELLS: u32 = 1 << 24;
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            GameError::InvalidPlayer => "it is not this player's turn",
+            GameError::InvalidPosition => "the position is outside the board",
+            GameError::PositionAlreadyOccupied => "the position is already occupied",
+            GameError::InvalidMove => "the move is not a letter",
+            GameError::GameFinished => "the game is already over",
+            GameError::InvalidBoardSize => "the board is empty or too large",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// With the `serde` feature a game is stored as the versioned schema in `schema`.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::schema::SavedGame", try_from = "crate::schema::SavedGame"))]
+pub struct Game {
+    num_of_players: u8,
+    rules: Rules,
+    turn : u8,
+    scores: Vec<u32>,
+    row: u16,
+    col: u16,
+    total: u32,
+    cells: Vec<CellValue>,
+    // The same cells as bitsets, for counting SOS quickly.
+    bits: BitBoard,
+    // The threats of every cell and the hot and dangerous ones among them, kept up to date on
+    // every change of the board.
+    threats: Vec<Threat>,
+    hot: BTreeSet<u32>,
+    dangerous: BTreeSet<u32>,
+    // Zobrist hash of the setup, the cells, the player to move and the score differences.
+    hash: u64,
+    total_occupied: u32,
+    sos: Vec<(u32, u32, u32)>,
+    history: Vec<MoveRecord>,
+    // Number of moves in `history` currently applied to the board, the rest can be redone.
+    played: usize,
+    // The position the game was set up from in notation, `None` when it started empty.
+    setup: Option<String>,
+}
+
+
+
+impl Game {
+    /// Starts a game on an empty board of `row` by `col` cells, with at most `MAX_CELLS` cells.
+    pub fn new(row:u16, col: u16, players: u8, rules: Rules) -> Result<Self, GameError> {
+        let total = row as u32 * col as u32;
+        if total == 0 || total > MAX_CELLS {
+            return Err(GameError::InvalidBoardSize);
+        }
+
+        let mut game = Self {
+            num_of_players: players,
+            rules,
+            turn: 0,
+            scores: vec![0;players as usize],
+            row,
+            col,
+            total,
+            cells: vec![CellValue::Empty;total as usize],
+            bits: BitBoard::new(row, col),
+            threats: vec![Threat::default(); total as usize],
+            hot: BTreeSet::new(),
+            dangerous: BTreeSet::new(),
+            hash: 0,
+            total_occupied: 0,
+            sos: vec![],
+            history: vec![],
+            played: 0,
+            setup: None,
+        };
+        game.hash = game.setup_key() ^ zobrist::state_key(game.turn, &game.scores);
+        Ok(game)
+    }
+
+    /// Sets up a position without history. Every SOS on the board is taken as already scored.
+    /// The board has to be a valid size with `cells` matching it. Unless it is the start of a new
+    /// game, the position is kept as the setup of the game.
+    pub(crate) fn from_position(row: u16, col: u16, rules: Rules, cells: Vec<CellValue>, turn: u8, scores: Vec<u32>) -> Result<Self, GameError> {
+        let mut game = Game::new(row, col, scores.len() as u8, rules)?;
+        game.turn = turn;
+        game.scores = scores;
+        game.cells = cells;
+        game.bits = BitBoard::from_board(&game.board());
+        game.hash = game.setup_key() ^ zobrist::state_key(game.turn, &game.scores);
+        for (pos, &value) in game.cells.iter().enumerate() {
+            game.hash ^= zobrist::cell_key(pos as u32, value);
+        }
+        // Only cells next to a letter can have threats.
+        for pos in 0..game.total {
+            if game.cells[pos as usize] != CellValue::Empty {
+                game.update_threats_around(pos);
+            }
+        }
+        game.total_occupied = game.cells.iter().filter(|&&cell| cell != CellValue::Empty).count() as u32;
+        for pos in 0..game.total {
+            if game.cells[pos as usize] == CellValue::O {
+                let sos = game.add_o(pos);
+                game.sos.extend(sos);
+            }
+        }
+        if game.total_occupied > 0 || game.turn != 0 {
+            game.setup = Some(game.to_notation());
+        }
+        Ok(game)
+    }
+
+    pub fn update(&mut self, player:u8, pos:u32, value: CellValue) -> Result<UpdateResponse, GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameFinished);
+        }
+        if pos >= self.total {
+            return Err(GameError::InvalidPosition);
+        }
+        if self.cells[pos as usize] != CellValue::Empty {
+            return Err(GameError::PositionAlreadyOccupied);
+        }
+        if self.turn != player {
+            return Err(GameError::InvalidPlayer);
+        }
+
+        if  player >= self.num_of_players {
+            return Err(GameError::InvalidPlayer);
+        }
+
+        if value == CellValue::Empty {
+            return Err(GameError::InvalidMove);
+        }
+
+        let ret = self.play(player, pos, value);
+        self.history.truncate(self.played);
+        self.history.push(MoveRecord { player, pos, value, sos: ret.clone() });
+        self.played += 1;
+        Ok(UpdateResponse {
+            next_turn: self.turn,
+            scores: self.scores.clone(),
+            new_sos: ret,
+        })
+    }
+
+    /// Plays a move for the player to move.
+    pub fn make_move(&mut self, pos: u32, value: CellValue) -> Result<UpdateResponse, GameError> {
+        self.update(self.turn, pos, value)
+    }
+
+    /// Every move the player to move can make, none once the game is over.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (u32, CellValue)> + '_ {
+        let empty = if self.is_game_over() { 0 } else { self.total };
+        (0..empty)
+            .filter(|&pos| self.cells[pos as usize] == CellValue::Empty)
+            .flat_map(|pos| [(pos, CellValue::S), (pos, CellValue::O)])
+    }
+
+    /// The SOS that playing `value` at the empty cell `pos` would complete.
+    pub fn get_sos_for_move(&self, pos: u32, value: CellValue) -> Vec<(u32, u32, u32)> {
+        match value {
+            CellValue::S => self.add_s(pos),
+            CellValue::O => self.add_o(pos),
+            CellValue::Empty => vec![],
+        }
+    }
+
+    /// The number of SOS that playing `value` at the empty cell `pos` would complete, counted
+    /// without allocating.
+    pub fn count_sos_for_move(&self, pos: u32, value: CellValue) -> usize {
+        self.bits.count_sos(pos, value)
+    }
+
+    /// Takes back the last played move and returns it, the move can be replayed with `redo`.
+    pub fn undo(&mut self) -> Option<MoveRecord> {
+        if self.played == 0 {
+            return None;
+        }
+        self.played -= 1;
+        let record = self.history[self.played].clone();
+        self.cells[record.pos as usize] = CellValue::Empty;
+        self.bits.set(record.pos, CellValue::Empty);
+        self.update_threats_around(record.pos);
+        self.hash ^= zobrist::cell_key(record.pos, record.value) ^ zobrist::state_key(self.turn, &self.scores);
+        self.scores[record.player as usize] -= record.sos.len() as u32;
+        self.turn = record.player;
+        self.hash ^= zobrist::state_key(self.turn, &self.scores);
+        self.sos.truncate(self.sos.len() - record.sos.len());
+        self.total_occupied -= 1;
+        Some(record)
+    }
+
+    /// Replays the last move taken back by `undo`.
+    pub fn redo(&mut self) -> Option<MoveRecord> {
+        if self.played >= self.history.len() {
+            return None;
+        }
+        let record = self.history[self.played].clone();
+        self.play(record.player, record.pos, record.value);
+        self.played += 1;
+        Some(record)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.played > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.played < self.history.len()
+    }
+
+    /// The moves currently on the board, in the order they were played.
+    pub fn get_history(&self) -> &[MoveRecord] {
+        &self.history[..self.played]
+    }
+
+    /// The moves taken back by `undo` that `redo` would replay, next one first.
+    pub fn get_redo_moves(&self) -> &[MoveRecord] {
+        &self.history[self.played..]
+    }
+
+    /// The position the game was set up from in the notation of `notation`, or `None` for a game
+    /// started on an empty board. Undoing every move goes back to this position.
+    pub fn get_setup(&self) -> Option<&str> {
+        self.setup.as_deref()
+    }
+
+    pub fn get_sos(&self) -> &[(u32, u32, u32)] {
+        &self.sos
+    }
+
+    fn play(&mut self, player:u8, pos:u32, value: CellValue) -> Vec<(u32, u32, u32)> {
+        let ret = if value == CellValue::S {
+            self.add_s(pos)
+        }else {
+            self.add_o(pos)
+        };
+        self.cells[pos as usize] = value;
+        self.bits.set(pos, value);
+        self.update_threats_around(pos);
+        self.hash ^= zobrist::cell_key(pos, value) ^ zobrist::state_key(self.turn, &self.scores);
+        self.scores[player as usize] += ret.len() as u32;
+        let extra_turn = self.rules.extra_turn_on_sos && !ret.is_empty();
+        if !extra_turn {
+            self.turn = (self.turn + 1) % self.num_of_players;
+        }
+        self.hash ^= zobrist::state_key(self.turn, &self.scores);
+        self.sos.extend_from_slice(&ret);
+        self.total_occupied += 1;
+        ret
+    }
+
+    /// The SOS an S at `pos` would complete, with the other S two cells away in any direction.
+    pub(crate) fn add_s(&self, pos: u32) -> Vec<(u32, u32, u32)> {
+        let board = self.board();
+        let mut ret : Vec<(u32, u32, u32)> = vec![];
+        for step in NEIGHBOURS {
+            let (Some(middle), Some(end)) = (board.neighbour(pos, step, 1), board.neighbour(pos, step, 2)) else {
+                continue;
+            };
+            if self.cells[middle as usize] == CellValue::O && self.cells[end as usize] == CellValue::S {
+                ret.push((pos.min(end), middle, pos.max(end)));
+            }
+        }
+        ret
+    }
+
+    /// The SOS an O at `pos` would complete, with an S on both sides.
+    pub(crate) fn add_o(&self, pos: u32) -> Vec<(u32, u32, u32)> {
+        let board = self.board();
+        let mut ret : Vec<(u32, u32, u32)> = vec![];
+        // Each line through the cell once, the S before it in reading order comes first.
+        for step in [(1, 1), (1, 0), (1, -1), (0, 1)] {
+            let (Some(start), Some(end)) = (board.neighbour(pos, step, -1), board.neighbour(pos, step, 1)) else {
+                continue;
+            };
+            if self.cells[start as usize] == CellValue::S && self.cells[end as usize] == CellValue::S {
+                ret.push((start, pos, end));
+            }
+        }
+        ret
+    }
+
+    fn setup_key(&self) -> u64 {
+        zobrist::setup_key(self.row, self.col, self.num_of_players, self.rules)
+    }
+
+    // A letter only changes the threats of the cells in lines of three through it, which are at
+    // most two rows and two columns away.
+    fn update_threats_around(&mut self, pos: u32) {
+        let (rows, cols) = (self.row as u32, self.col as u32);
+        let (row, col) = (pos / cols, pos % cols);
+        for r in row.saturating_sub(2)..=(row + 2).min(rows - 1) {
+            for c in col.saturating_sub(2)..=(col + 2).min(cols - 1) {
+                self.update_threat(r * cols + c);
+            }
+        }
+    }
+
+    fn update_threat(&mut self, pos: u32) {
+        let threat = self.bits.threat(pos);
+        let old = std::mem::replace(&mut self.threats[pos as usize], threat);
+        if threat.is_hot() != old.is_hot() {
+            if threat.is_hot() {
+                self.hot.insert(pos);
+            } else {
+                self.hot.remove(&pos);
+            }
+        }
+        let is_dangerous = |threat: Threat| threat.s_dangerous || threat.o_dangerous;
+        if is_dangerous(threat) != is_dangerous(old) {
+            if is_dangerous(threat) {
+                self.dangerous.insert(pos);
+            } else {
+                self.dangerous.remove(&pos);
+            }
+        }
+    }
+
+    /// What each letter would do at `pos` right now, nothing for occupied cells.
+    pub fn get_threat(&self, pos: u32) -> Threat {
+        self.threats.get(pos as usize).copied().unwrap_or_default()
+    }
+
+    /// The empty cells where a letter completes an SOS, in order, with their threats.
+    pub fn hot_cells(&self) -> impl Iterator<Item = (u32, Threat)> + '_ {
+        self.hot.iter().map(|&pos| (pos, self.threats[pos as usize]))
+    }
+
+    /// The empty cells where at least one letter hands the next player an SOS, in order, with
+    /// their threats.
+    pub fn dangerous_cells(&self) -> impl Iterator<Item = (u32, Threat)> + '_ {
+        self.dangerous.iter().map(|&pos| (pos, self.threats[pos as usize]))
+    }
+
+    /// Lets the strategy pick the move for the player to move and plays it.
+    pub fn play_strategy(&mut self, strategy: &mut dyn Strategy) -> Result<(u32, CellValue, UpdateResponse), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameFinished);
+        }
+        let (pos, val) = strategy.choose_move(self).ok_or(GameError::InvalidMove)?;
+        let res = self.update(self.turn, pos, val)?;
+        Ok((pos, val, res))
+    }
+
+    pub fn board(&self) -> Board<'_> {
+        Board::new(self.row, self.col, &self.cells)
+    }
+
+    /// A 64-bit Zobrist hash of the position: the board size, players and rules, the letters on
+    /// the board, the player to move and the differences between the scores. Positions reached by
+    /// different move orders have the same hash, the history is not part of it.
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn get_rows(&self) -> u16 {
+        self.row
+    }
+
+    pub fn get_cols(&self) -> u16 {
+        self.col
+    }
+
+    pub fn get_num_of_players(&self) -> u8 {
+        self.num_of_players
+    }
+
+    pub fn get_scores(&self) -> Vec<u32> {
+        self.scores.clone()
+    }
+
+    /// The score of `player` minus the best score of the other players, positive when they lead.
+    pub fn margin(&self, player: u8) -> i32 {
+        let best_other = self.scores.iter()
+            .enumerate()
+            .filter(|&(other, _)| other != player as usize)
+            .map(|(_, &score)| score)
+            .max()
+            .unwrap_or(0);
+        self.scores[player as usize] as i32 - best_other as i32
+    }
+
+    pub fn get_current_turn(&self) -> u8 {
+        self.turn
+    }
+
+    pub fn get_rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        if self.rules.mode == GameMode::Simple && !self.sos.is_empty() {
+            return true;
+        }
+        self.total_occupied >= self.total
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.is_game_over() {
+            return None;
+        }
+        if self.rules.mode == GameMode::Simple {
+            let winner = self.scores.iter().position(|&score| score > 0);
+            return Some(match winner {
+                Some(player) => Outcome::Win { player: player as u8, reason: WinReason::FirstSos },
+                None => Outcome::Draw { players: (0..self.num_of_players).collect(), reason: DrawReason::NoSos },
+            });
+        }
+
+        let best = self.scores.iter().copied().max().unwrap_or(0);
+        let leaders: Vec<u8> = (0..self.num_of_players)
+            .filter(|&player| self.scores[player as usize] == best)
+            .collect();
+        if leaders.len() == 1 {
+            Some(Outcome::Win { player: leaders[0], reason: WinReason::MostSos })
+        } else {
+            Some(Outcome::Draw { players: leaders, reason: DrawReason::EqualScores })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(notation: &str) -> Game {
+        Game::from_notation(notation).expect("valid position")
+    }
+
+    fn sorted(mut sos: Vec<(u32, u32, u32)>) -> Vec<(u32, u32, u32)> {
+        sos.sort();
+        sos
+    }
+
+    #[test]
+    fn update_rejects_the_wrong_player() {
+        let mut game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        assert!(matches!(game.update(1, 0, CellValue::S), Err(GameError::InvalidPlayer)));
+        assert!(matches!(game.update(2, 0, CellValue::S), Err(GameError::InvalidPlayer)));
+        assert!(game.update(0, 0, CellValue::S).is_ok());
+        assert!(matches!(game.update(0, 1, CellValue::S), Err(GameError::InvalidPlayer)));
+    }
+
+    #[test]
+    fn update_rejects_positions_outside_the_board() {
+        let mut game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        assert!(matches!(game.update(0, 9, CellValue::S), Err(GameError::InvalidPosition)));
+        assert!(matches!(game.update(0, u32::MAX, CellValue::O), Err(GameError::InvalidPosition)));
+    }
+
+    #[test]
+    fn update_rejects_occupied_cells() {
+        let mut game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        game.update(0, 4, CellValue::O).unwrap();
+        assert!(matches!(game.update(1, 4, CellValue::S), Err(GameError::PositionAlreadyOccupied)));
+        assert_eq!(game.get_current_turn(), 1);
+    }
+
+    #[test]
+    fn update_rejects_empty_letters() {
+        let mut game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        assert!(matches!(game.update(0, 0, CellValue::Empty), Err(GameError::InvalidMove)));
+        assert!(game.get_history().is_empty());
+    }
+
+    #[test]
+    fn update_rejects_moves_after_the_game() {
+        let mut game = Game::new(1, 2, 2, Rules::default()).unwrap();
+        game.update(0, 0, CellValue::S).unwrap();
+        game.update(1, 1, CellValue::O).unwrap();
+        assert!(game.is_game_over());
+        assert!(matches!(game.update(0, 0, CellValue::S), Err(GameError::GameFinished)));
+
+        let mut simple = position("1x4 SO2 1 0,0 simple");
+        simple.update(0, 2, CellValue::S).unwrap();
+        assert!(simple.is_game_over());
+        assert!(matches!(simple.update(1, 3, CellValue::S), Err(GameError::GameFinished)));
+    }
+
+    #[test]
+    fn add_s_finds_sos_from_the_corners() {
+        // S O S along the top row and the left column, completed at a1.
+        let game = position("3x3 1OS/O2/S2 1 0,0");
+        assert_eq!(sorted(game.add_s(0)), vec![(0, 1, 2), (0, 3, 6)]);
+        let game = position("3x3 3/1O1/2S 1 0,0");
+        assert_eq!(game.add_s(0), vec![(0, 4, 8)]);
+        // The same lines completed from the other corners.
+        let game = position("3x3 SO1/3/3 1 0,0");
+        assert_eq!(game.add_s(2), vec![(0, 1, 2)]);
+        let game = position("3x3 S2/1O1/3 1 0,0");
+        assert_eq!(game.add_s(8), vec![(0, 4, 8)]);
+        let game = position("3x3 2S/1O1/3 1 0,0");
+        assert_eq!(game.add_s(6), vec![(2, 4, 6)]);
+    }
+
+    #[test]
+    fn add_o_finds_sos_on_the_edges() {
+        let game = position("3x3 S1S/3/3 1 0,0");
+        assert_eq!(game.add_o(1), vec![(0, 1, 2)]);
+        let game = position("3x3 S2/3/S2 1 0,0");
+        assert_eq!(game.add_o(3), vec![(0, 3, 6)]);
+        let game = position("3x3 S1S/3/S1S 1 0,0");
+        assert_eq!(sorted(game.add_o(4)), vec![(0, 4, 8), (2, 4, 6)]);
+    }
+
+    #[test]
+    fn sos_does_not_wrap_around_rows() {
+        // c1 and a2 are next to each other in memory but not on the board.
+        let game = position("3x3 2S/1S1/3 1 0,0");
+        assert!(game.add_o(3).is_empty());
+        let game = position("3x3 1SO/3/3 1 0,0");
+        assert!(game.add_s(3).is_empty());
+        let game = position("2x4 3S/O3 1 0,0");
+        assert!(game.add_s(5).is_empty());
+        // Diagonals that would leave the board on one side and come back on the other.
+        let game = position("3x3 1S1/2O/1S1 1 0,0");
+        assert!(game.add_s(3).is_empty());
+        let game = position("4x3 3/3/O2/1S1 1 0,0");
+        assert!(game.add_s(2).is_empty());
+    }
+
+    #[test]
+    fn sos_on_boards_one_cell_wide_is_counted_once() {
+        let game = position("3x1 S/1/S 1 0,0");
+        assert_eq!(game.add_o(1), vec![(0, 1, 2)]);
+        let game = position("1x3 S1S 1 0,0");
+        assert_eq!(game.add_o(1), vec![(0, 1, 2)]);
+        assert!(Game::new(1, 1, 2, Rules::default()).unwrap().add_s(0).is_empty());
+    }
+
+    #[test]
+    fn new_rejects_empty_and_oversized_boards() {
+        assert!(matches!(Game::new(0, 5, 2, Rules::default()), Err(GameError::InvalidBoardSize)));
+        assert!(matches!(Game::new(5, 0, 2, Rules::default()), Err(GameError::InvalidBoardSize)));
+        assert!(matches!(Game::new(u16::MAX, u16::MAX, 2, Rules::default()), Err(GameError::InvalidBoardSize)));
+        assert!(matches!(Game::new(4097, 4096, 2, Rules::default()), Err(GameError::InvalidBoardSize)));
+        assert_eq!(Game::new(4096, 4096, 2, Rules::default()).unwrap().board().get_num_of_cells(), MAX_CELLS);
+    }
+
+    #[test]
+    fn sos_is_found_on_large_boards() {
+        let last = 999_999;
+        let mut cells = vec![CellValue::Empty; 1_000_000];
+        // The bottom-right corner, across the last row, up the last column and on the diagonal.
+        for pos in [last - 1, last - 1000, last - 1001] {
+            cells[pos as usize] = CellValue::O;
+        }
+        for pos in [last - 2, last - 2000, last - 2002] {
+            cells[pos as usize] = CellValue::S;
+        }
+        // Nothing wraps from the end of one row to the start of the next.
+        cells[1000] = CellValue::O;
+        cells[1001] = CellValue::S;
+        let mut game = Game::from_position(1000, 1000, Rules::default(), cells, 0, vec![0, 0]).unwrap();
+        assert_eq!(sorted(game.add_s(last)), vec![(last - 2002, last - 1001, last), (last - 2000, last - 1000, last), (last - 2, last - 1, last)]);
+        assert!(game.add_s(999).is_empty());
+        game.make_move(last, CellValue::S).unwrap();
+        assert_eq!(game.get_scores(), vec![3, 0]);
+    }
+
+    #[test]
+    fn scoring_passes_the_turn_unless_there_is_an_extra_turn() {
+        let mut game = position("1x3 SO1 1 0,0 classic");
+        let response = game.update(0, 2, CellValue::S).unwrap();
+        assert_eq!(response.new_sos, vec![(0, 1, 2)]);
+        assert_eq!(response.next_turn, 0);
+        assert_eq!(game.get_scores(), vec![1, 0]);
+
+        let mut game = position("1x4 SO2 1 0,0 scoring");
+        assert_eq!(game.update(0, 2, CellValue::S).unwrap().next_turn, 1);
+    }
+
+    #[test]
+    fn threats_follow_the_moves() {
+        let mut game = position("1x5 S4 1 0,0");
+        assert_eq!(game.get_threat(1), Threat { s: 0, o: 0, s_dangerous: false, o_dangerous: true });
+        assert_eq!(game.get_threat(2), Threat { s: 0, o: 0, s_dangerous: true, o_dangerous: false });
+        assert_eq!(game.hot_cells().count(), 0);
+        assert_eq!(game.dangerous_cells().map(|(pos, _)| pos).collect::<Vec<_>>(), vec![1, 2]);
+
+        game.make_move(1, CellValue::O).unwrap();
+        assert_eq!(game.hot_cells().collect::<Vec<_>>(), vec![(2, Threat { s: 1, ..Threat::default() })]);
+        assert_eq!(game.get_threat(1), Threat::default());
+        assert_eq!(game.dangerous_cells().count(), 0);
+
+        game.undo();
+        assert_eq!(game.hot_cells().count(), 0);
+        assert_eq!(game.dangerous_cells().map(|(pos, _)| pos).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn transpositions_have_the_same_hash() {
+        let mut game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        let mut other = game.clone();
+        for (pos, value) in [(0, CellValue::S), (8, CellValue::O), (2, CellValue::S), (6, CellValue::O)] {
+            game.make_move(pos, value).unwrap();
+        }
+        for (pos, value) in [(2, CellValue::S), (6, CellValue::O), (0, CellValue::S), (8, CellValue::O)] {
+            other.make_move(pos, value).unwrap();
+        }
+        assert_eq!(game.get_hash(), other.get_hash());
+        assert_eq!(game.get_hash(), position("3x3 S1S/3/O1O 1 0,0").get_hash());
+
+        // The same letters with the other player to move, or with another score difference.
+        assert_ne!(game.get_hash(), position("3x3 S1S/3/O1O 2 0,0").get_hash());
+        assert_ne!(position("1x3 SOS 1 1,0").get_hash(), position("1x3 SOS 1 0,1").get_hash());
+
+        // The same letters on another board, with another number of players or other rules.
+        assert_ne!(position("3x3 S2/3/3 1 0,0").get_hash(), position("1x9 S8 1 0,0").get_hash());
+        assert_ne!(position("3x3 S2/3/3 1 0,0").get_hash(), position("3x3 S2/3/3 1 0,0,0").get_hash());
+        assert_ne!(position("3x3 S2/3/3 1 0,0").get_hash(), position("3x3 S2/3/3 1 0,0 simple").get_hash());
+        assert_ne!(position("3x3 S2/3/3 1 0,0 simple").get_hash(), position("3x3 S2/3/3 1 0,0 simple+extra").get_hash());
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_game() {
+        let mut game = position("1x4 SO2 2 0,0 classic");
+        game.update(1, 2, CellValue::S).unwrap();
+        let after = game.clone();
+        let record = game.undo().unwrap();
+        assert_eq!(record.sos, vec![(0, 1, 2)]);
+        assert_eq!(game.get_scores(), vec![0, 0]);
+        assert_eq!(game.get_current_turn(), 1);
+        assert!(game.get_sos().is_empty());
+        game.redo().unwrap();
+        assert_eq!(game, after);
+    }
+}
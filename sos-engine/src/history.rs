@@ -0,0 +1,11 @@
+use crate::cell::CellValue;
+
+/// A move as it was played, together with the SOS it completed.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveRecord {
+    pub player: u8,
+    pub pos: u32,
+    pub value: CellValue,
+    pub sos: Vec<(u32, u32, u32)>,
+}
@@ -0,0 +1,52 @@
+//! The rules, bots and file formats of the SOS game, without any user interface.
+//!
+//! A game is created with `Game::new` and played with `Game::make_move`, or with
+//! `Controller` when some seats are played by a `Strategy` such as `Bot` or `Mcts`. The cells
+//! are read through the `Board` view returned by `Game::board`. Boards can have up to
+//! `MAX_CELLS` cells, e.g. 1000 by 1000.
+//!
+//! ```
+//! use sos_engine::{CellValue, Game, Rules};
+//!
+//! let mut game = Game::new(3, 3, 2, Rules::classic()).unwrap();
+//! game.make_move(0, CellValue::S).unwrap();
+//! game.make_move(1, CellValue::O).unwrap();
+//! let response = game.make_move(2, CellValue::S).unwrap();
+//! assert_eq!(response.new_sos, vec![(0, 1, 2)]);
+//! assert_eq!(game.legal_moves().count(), 12);
+//! ```
+
+pub mod game;
+pub mod board;
+pub mod bitboard;
+pub mod cell;
+pub mod rules;
+pub mod outcome;
+pub mod threat;
+pub mod history;
+pub mod notation;
+pub mod record;
+pub mod share;
+pub mod protocol;
+pub mod difficulty;
+pub mod search;
+pub mod solver;
+pub mod mcts;
+pub mod strategy;
+pub mod controller;
+pub mod bot;
+pub mod transposition;
+mod zobrist;
+#[cfg(feature = "serde")]
+pub mod schema;
+
+pub use board::Board;
+pub use bot::Bot;
+pub use cell::CellValue;
+pub use controller::{Controller, Seat};
+pub use difficulty::Difficulty;
+pub use game::{Game, GameError, UpdateResponse, MAX_CELLS};
+pub use outcome::{DrawReason, Outcome, PlayerResult, WinReason};
+pub use rules::{GameMode, Rules};
+pub use strategy::Strategy;
+pub use threat::Threat;
@@ -0,0 +1,402 @@
+use crate::cell::CellValue;
+use crate::game::Game;
+use crate::outcome::PlayerResult;
+use crate::rules::GameMode;
+use crate::solver::{Solver, SolverLimits};
+use crate::strategy::{move_rng, Strategy};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+const SAFE_MOVE_ATTEMPTS: u8 = 4;
+// Random draws for an untried quiet move before listing all of them.
+const QUIET_MOVE_ATTEMPTS: u32 = 32;
+const WIDENING: f64 = 2.0;
+const MARGIN_SCALE: f64 = 4.0;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MctsConfig {
+    /// Number of select, expand, playout and backpropagate rounds per move.
+    pub iterations: u32,
+    /// UCT exploration constant, `sqrt(2)` is the textbook value.
+    pub exploration: f64,
+    /// Playouts complete an SOS whenever one is on offer instead of playing purely at random.
+    pub guided_playouts: bool,
+    /// Playouts stop after this many moves and the position is judged by the score margin.
+    /// Short playouts are much less noisy on large boards, `None` plays every game to the end.
+    pub playout_depth: Option<u32>,
+    /// Endgames within these limits are solved exactly with `Solver` instead, `None` never does.
+    pub solver: Option<SolverLimits>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 2_000,
+            exploration: std::f64::consts::SQRT_2,
+            guided_playouts: true,
+            playout_depth: Some(20),
+            solver: Some(SolverLimits { cells: 10, nodes: 200_000 }),
+        }
+    }
+}
+
+impl Strategy for Mcts {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        self.best_move(game)
+    }
+}
+
+struct Node {
+    mv: Option<(u32, CellValue)>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // Moves expanded before any quiet one, from the back: the moves completing an SOS with the
+    // biggest last, or a single move handing over an SOS when every move does.
+    untried: Vec<(u32, CellValue)>,
+    // Number of quiet moves, neither completing nor handing over an SOS, not expanded yet. They
+    // are drawn at random when needed, so a node does not list every empty cell.
+    quiet: u32,
+    // The player who made `mv`, rewards are counted from their point of view.
+    player: u8,
+    visits: u32,
+    reward: f64,
+}
+
+// The empty cells of the searched game, in a list that moves take cells out of and undoing them
+// puts them back into, both in constant time.
+struct EmptyCells {
+    cells: Vec<u32>,
+    // Index of every empty cell in `cells`.
+    slots: Vec<u32>,
+}
+
+impl EmptyCells {
+    fn new(game: &Game) -> Self {
+        let cells: Vec<u32> = game.board().empty_cells().collect();
+        let mut slots = vec![0; game.board().get_num_of_cells() as usize];
+        for (slot, &pos) in cells.iter().enumerate() {
+            slots[pos as usize] = slot as u32;
+        }
+        Self { cells, slots }
+    }
+
+    fn remove(&mut self, pos: u32) {
+        let slot = self.slots[pos as usize] as usize;
+        self.cells.swap_remove(slot);
+        if let Some(&moved) = self.cells.get(slot) {
+            self.slots[moved as usize] = slot as u32;
+        }
+    }
+
+    // Cells have to be put back in the reverse order they were removed in.
+    fn restore(&mut self, pos: u32) {
+        let slot = self.slots[pos as usize] as usize;
+        self.cells.push(pos);
+        let last = self.cells.len() - 1;
+        self.cells.swap(slot, last);
+        self.slots[self.cells[last] as usize] = last as u32;
+    }
+}
+
+/// Monte Carlo Tree Search player with UCT selection.
+///
+/// It only plays short random games from the position, so its cost per move grows with the
+/// iterations and the playout depth rather than exponentially with the search depth. Every
+/// iteration plays on a single copy of the game and takes its moves back afterwards.
+pub struct Mcts {
+    config: MctsConfig,
+    seed: u64,
+    // Drawn from the seed and the position at the start of every search.
+    rng: StdRng,
+    solver: Option<Solver>,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig, seed: u64) -> Self {
+        Self {
+            config,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            solver: None,
+        }
+    }
+
+    pub fn best_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        if game.is_game_over() {
+            return None;
+        }
+        self.rng = move_rng(self.seed, game);
+        if let Some(limits) = self.config.solver.filter(|limits| game.board().count_empty() as u32 <= limits.cells) {
+            let solver = self.solver.get_or_insert_with(|| Solver::new(limits));
+            if let Some(solution) = solver.solve(game) {
+                return solution.moves.choose(&mut self.rng).copied();
+            }
+        }
+        let nodes = self.search(game);
+        let mean = |node: &Node| node.reward / node.visits.max(1) as f64;
+        nodes[0].children.iter()
+            .max_by(|&&a, &&b| {
+                nodes[a].visits.cmp(&nodes[b].visits).then(mean(&nodes[a]).total_cmp(&mean(&nodes[b])))
+            })
+            .and_then(|&child| nodes[child].mv)
+    }
+
+    // Runs the configured number of iterations, at least one so that a running game always gets
+    // a move, and returns the tree with the root first.
+    fn search(&mut self, game: &Game) -> Vec<Node> {
+        let mut state = game.clone();
+        let mut empty = EmptyCells::new(&state);
+        let mut nodes = vec![self.new_node(&state, &empty, None, None, state.get_current_turn())];
+
+        for _ in 0..self.config.iterations.max(1) {
+            // Selection: walk down until a node can take another child.
+            let mut current = 0;
+            let mut moves = 0;
+            while !Mcts::can_expand(&nodes[current]) && !nodes[current].children.is_empty() {
+                current = self.select_child(&nodes, current);
+                let (pos, value) = nodes[current].mv.expect("only the root has no move");
+                Mcts::play(&mut state, &mut empty, pos, value);
+                moves += 1;
+            }
+
+            // Expansion: add one untried move.
+            if let Some((pos, value)) = self.next_untried(&mut nodes, current, &state, &empty) {
+                let player = state.get_current_turn();
+                Mcts::play(&mut state, &mut empty, pos, value);
+                moves += 1;
+                let child = self.new_node(&state, &empty, Some((pos, value)), Some(current), player);
+                nodes.push(child);
+                let index = nodes.len() - 1;
+                nodes[current].children.push(index);
+                current = index;
+            }
+
+            moves += self.playout(&mut state, &mut empty);
+
+            // Backpropagation.
+            let mut node = Some(current);
+            while let Some(index) = node {
+                nodes[index].visits += 1;
+                nodes[index].reward += Mcts::reward(game, &state, nodes[index].player);
+                node = nodes[index].parent;
+            }
+
+            for _ in 0..moves {
+                let record = state.undo().expect("the moves of this iteration are on the board");
+                empty.restore(record.pos);
+            }
+        }
+        nodes
+    }
+
+    fn play(game: &mut Game, empty: &mut EmptyCells, pos: u32, value: CellValue) {
+        game.update(game.get_current_turn(), pos, value).expect("only empty cells of a running game are played");
+        empty.remove(pos);
+    }
+
+    fn new_node(&mut self, game: &Game, empty: &EmptyCells, mv: Option<(u32, CellValue)>, parent: Option<usize>, player: u8) -> Node {
+        let mut untried = vec![];
+        let mut quiet = 0;
+        if !game.is_game_over() {
+            // Playouts are too noisy to reliably value a single SOS, so when one is on offer only
+            // the moves taking it are considered.
+            let mut scoring = vec![];
+            for (pos, threat) in game.hot_cells() {
+                for value in [CellValue::S, CellValue::O] {
+                    if threat.count(value) > 0 {
+                        scoring.push((pos, value, threat.count(value)));
+                    }
+                }
+            }
+            // Moves are expanded from the back, so the biggest SOS are tried first.
+            scoring.shuffle(&mut self.rng);
+            scoring.sort_by_key(|&(_, _, gain)| gain);
+            untried = scoring.into_iter().map(|(pos, value, _)| (pos, value)).collect();
+
+            // Random playouts cannot tell that handing over an SOS lets the opponent chain several
+            // of them, so such moves are pruned unless nothing else is left.
+            if untried.is_empty() {
+                let dangerous: u32 = game.dangerous_cells()
+                    .map(|(_, threat)| threat.s_dangerous as u32 + threat.o_dangerous as u32)
+                    .sum();
+                quiet = 2 * empty.cells.len() as u32 - dangerous;
+                if quiet == 0 {
+                    untried.push(self.random_move(&empty.cells));
+                }
+            }
+        }
+        Node {
+            mv,
+            parent,
+            children: vec![],
+            untried,
+            quiet,
+            player,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    // Progressive widening: a node gets more children only as it is visited more often, so on
+    // large boards the iterations are not spread over hundreds of barely visited moves.
+    fn can_expand(node: &Node) -> bool {
+        let limit = 1 + (WIDENING * (node.visits as f64).sqrt()) as usize;
+        if node.children.len() >= limit {
+            return false;
+        }
+        !node.untried.is_empty() || node.quiet > 0
+    }
+
+    fn next_untried(&mut self, nodes: &mut [Node], current: usize, game: &Game, empty: &EmptyCells) -> Option<(u32, CellValue)> {
+        if let Some(mv) = nodes[current].untried.pop() {
+            return Some(mv);
+        }
+        if nodes[current].quiet == 0 {
+            return None;
+        }
+        nodes[current].quiet -= 1;
+        let tried: Vec<(u32, CellValue)> = nodes[current].children.iter().filter_map(|&child| nodes[child].mv).collect();
+        let is_untried = |&(pos, value): &(u32, CellValue)| {
+            !game.get_threat(pos).is_dangerous(value) && !tried.contains(&(pos, value))
+        };
+        for _ in 0..QUIET_MOVE_ATTEMPTS {
+            let mv = self.random_move(&empty.cells);
+            if is_untried(&mv) {
+                return Some(mv);
+            }
+        }
+        // Few quiet moves are left, so pick among all of them.
+        let moves: Vec<(u32, CellValue)> = empty.cells.iter()
+            .flat_map(|&pos| [(pos, CellValue::S), (pos, CellValue::O)])
+            .filter(is_untried)
+            .collect();
+        moves.choose(&mut self.rng).copied()
+    }
+
+    fn select_child(&self, nodes: &[Node], parent: usize) -> usize {
+        let log_visits = (nodes[parent].visits.max(1) as f64).ln();
+        let uct = |child: usize| {
+            let node = &nodes[child];
+            let visits = node.visits.max(1) as f64;
+            node.reward / visits + self.config.exploration * (log_visits / visits).sqrt()
+        };
+        nodes[parent].children.iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .expect("fully expanded node has children")
+    }
+
+    // Returns the number of moves played.
+    fn playout(&mut self, game: &mut Game, empty: &mut EmptyCells) -> usize {
+        let mut depth = 0;
+        while !game.is_game_over() && !empty.cells.is_empty() && self.config.playout_depth.is_none_or(|max| depth < max) {
+            depth += 1;
+            let (pos, value) = self.playout_move(game, &empty.cells);
+            Mcts::play(game, empty, pos, value);
+        }
+        depth as usize
+    }
+
+    fn playout_move(&mut self, game: &Game, empty: &[u32]) -> (u32, CellValue) {
+        if !self.config.guided_playouts {
+            return self.random_move(empty);
+        }
+        let hot = game.hot_cells().count();
+        if hot > 0 {
+            let (pos, threat) = game.hot_cells().nth(self.rng.gen_range(0..hot)).expect("counted above");
+            let value = if threat.s > 0 { CellValue::S } else { CellValue::O };
+            return (pos, value);
+        }
+        // Prefer a quiet move that does not hand the next player an SOS, if one turns up quickly.
+        let mut mv = self.random_move(empty);
+        for _ in 0..SAFE_MOVE_ATTEMPTS {
+            if !game.get_threat(mv.0).is_dangerous(mv.1) {
+                break;
+            }
+            mv = self.random_move(empty);
+        }
+        mv
+    }
+
+    fn random_move(&mut self, empty: &[u32]) -> (u32, CellValue) {
+        let value = if self.rng.gen_bool(0.5) { CellValue::S } else { CellValue::O };
+        (empty[self.rng.gen_range(0..empty.len())], value)
+    }
+
+    // A finished simple game is all about the result. Otherwise the margin gained since the
+    // root position is squashed into the 0..1 range, so a big lead does not flatten the rewards.
+    fn reward(root: &Game, game: &Game, player: u8) -> f64 {
+        if game.get_rules().mode == GameMode::Simple && game.is_game_over() {
+            return match game.outcome().map(|outcome| outcome.result_for(player)) {
+                Some(PlayerResult::Win) => 1.0,
+                Some(PlayerResult::Draw) => 0.5,
+                _ => 0.0,
+            };
+        }
+        let gained = (game.margin(player) - root.margin(player)) as f64;
+        0.5 + 0.5 * (gained / MARGIN_SCALE).tanh()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    // Small budgets without the endgame solver, so the tree search itself is tested.
+    fn config(iterations: u32) -> MctsConfig {
+        MctsConfig { iterations, solver: None, ..MctsConfig::default() }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_move() {
+        let game = Game::from_notation("5x5 S4/1O3/5/3O1/4S 1 0,0").unwrap();
+        let first = Mcts::new(config(300), 9).choose_move(&game);
+        assert!(first.is_some());
+        assert_eq!(Mcts::new(config(300), 9).choose_move(&game), first);
+    }
+
+    #[test]
+    fn completes_an_open_sos() {
+        for seed in 0..5 {
+            let game = Game::from_notation("4x4 SO2/4/4/4 1 0,0").unwrap();
+            assert_eq!(Mcts::new(config(200), seed).choose_move(&game), Some((2, CellValue::S)));
+            let game = Game::from_notation("4x4 4/S1S1/4/4 2 0,0").unwrap();
+            assert_eq!(Mcts::new(config(200), seed).choose_move(&game), Some((5, CellValue::O)));
+        }
+    }
+
+    #[test]
+    fn no_move_when_the_game_is_over() {
+        let game = Game::from_notation("1x3 SOS 1 1,0").unwrap();
+        assert_eq!(Mcts::new(config(100), 0).choose_move(&game), None);
+    }
+
+    #[test]
+    fn runs_the_configured_iterations() {
+        let game = Game::new(4, 4, 2, Rules::classic()).unwrap();
+        for iterations in [1, 50, 400] {
+            let nodes = Mcts::new(config(iterations), 3).search(&game);
+            assert_eq!(nodes[0].visits, iterations);
+            assert!(nodes.len() <= iterations as usize + 1);
+        }
+    }
+
+    #[test]
+    fn zero_iterations_still_give_a_move() {
+        let game = Game::new(4, 4, 2, Rules::classic()).unwrap();
+        assert!(Mcts::new(config(0), 3).choose_move(&game).is_some());
+    }
+
+    #[test]
+    fn large_boards_only_list_the_moves_tried() {
+        let mut game = Game::new(200, 200, 2, Rules::classic()).unwrap();
+        for (pos, value) in [(0, CellValue::S), (20_100, CellValue::O), (1, CellValue::O), (39_999, CellValue::S)] {
+            game.make_move(pos, value).unwrap();
+        }
+        let nodes = Mcts::new(config(300), 5).search(&game);
+        assert_eq!(nodes[0].visits, 300);
+        assert!(nodes.iter().all(|node| node.untried.len() <= 4));
+    }
+}
@@ -0,0 +1,354 @@
+//! Text notation for moves and positions.
+//!
+//! A square is written as its column letter followed by its row number, `a1` being the top-left
+//! corner. Columns after `z` continue with `aa`, `ab` and so on. A move is the letter and the
+//! square joined by `@`, e.g. `S@c4`.
+//!
+//! A position is written in five fields separated by spaces, similar to FEN in chess:
+//!
+//! ```text
+//! 3x4 SOS1/4/.S2 1 1,0 classic
+//! ```
+//!
+//! 1. The board size as `<rows>x<cols>`.
+//! 2. The rows from top to bottom separated by `/`. Each row lists its cells from left to right
+//!    as `S`, `O`, or a number counting consecutive empty cells. A single `.` may also be used for
+//!    an empty cell.
+//! 3. The player to move, counting from 1.
+//! 4. The scores of all players separated by `,`, which also gives the number of players.
+//! 5. The rules: `scoring`, `classic` (scoring with an extra turn after an SOS), `simple`, or
+//!    `simple+extra`. This field may be left out for the default `scoring` rules, nothing may
+//!    follow it.
+//!
+//! Every SOS on the board counts as already scored, so the scores have to add up to the number of
+//! SOS on the board.
+
+use crate::cell::CellValue;
+use crate::game::{Game, MAX_CELLS};
+use crate::rules::{GameMode, Rules};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum NotationError {
+    InvalidMove(String),
+    InvalidSquare(String),
+    InvalidDimensions(String),
+    /// The row, counting from 1, does not describe exactly one cell per column.
+    InvalidRow(usize),
+    InvalidPlayer(String),
+    InvalidScores(String),
+    InvalidRules(String),
+    /// The scores do not add up to the number of SOS on the board.
+    ScoreMismatch,
+    MissingField,
+    /// The position has more than five fields, this is the first extra one.
+    ExtraField(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::InvalidMove(text) => write!(f, "invalid move `{}`", text),
+            NotationError::InvalidSquare(text) => write!(f, "invalid square `{}`", text),
+            NotationError::InvalidDimensions(text) => write!(f, "invalid board size `{}`", text),
+            NotationError::InvalidRow(row) => write!(f, "row {} does not match the board width", row),
+            NotationError::InvalidPlayer(text) => write!(f, "invalid player to move `{}`", text),
+            NotationError::InvalidScores(text) => write!(f, "invalid scores `{}`", text),
+            NotationError::InvalidRules(text) => write!(f, "unknown rules `{}`", text),
+            NotationError::ScoreMismatch => f.write_str("the scores do not match the SOS on the board"),
+            NotationError::MissingField => f.write_str("the position is missing a field"),
+            NotationError::ExtraField(text) => write!(f, "unexpected field `{}`", text),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+// Reads a number written with digits only, `parse` would also take a leading `+`.
+pub(crate) fn parse_number<T: FromStr>(text: &str) -> Option<T> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Writes the letters of a column counting from 0, e.g. `c` for 2 and `aa` for 26.
+pub fn format_column(col: u16) -> String {
+    let mut column = col as u32 + 1;
+    let mut letters = vec![];
+    while column > 0 {
+        column -= 1;
+        letters.push(char::from_u32('a' as u32 + column % 26).expect("ascii letter"));
+        column /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Writes the square of `pos` on a board `cols` wide, e.g. `c4`.
+pub fn format_square(pos: u32, cols: u16) -> String {
+    format_column((pos % cols as u32) as u16) + &(pos / cols as u32 + 1).to_string()
+}
+
+/// Reads a square written by `format_square` on a board of `rows` by `cols`.
+pub fn parse_square(text: &str, rows: u16, cols: u16) -> Result<u32, NotationError> {
+    let error = || NotationError::InvalidSquare(text.to_string());
+    let split = text.find(|c: char| !c.is_ascii_alphabetic()).ok_or_else(error)?;
+    let (letters, number) = text.split_at(split);
+    if letters.is_empty() {
+        return Err(error());
+    }
+    let mut column: u32 = 0;
+    for c in letters.chars() {
+        column = column * 26 + (c.to_ascii_lowercase() as u32 - 'a' as u32 + 1);
+        if column > cols as u32 {
+            return Err(error());
+        }
+    }
+    let row: u16 = parse_number(number).ok_or_else(error)?;
+    if row == 0 || row > rows {
+        return Err(error());
+    }
+    Ok((row as u32 - 1) * cols as u32 + column - 1)
+}
+
+/// The name of the rules in the last field of a position, e.g. `classic`.
+pub fn format_rules(rules: Rules) -> &'static str {
+    match (rules.mode, rules.extra_turn_on_sos) {
+        (GameMode::Scoring, false) => "scoring",
+        (GameMode::Scoring, true) => "classic",
+        (GameMode::Simple, false) => "simple",
+        (GameMode::Simple, true) => "simple+extra",
+    }
+}
+
+pub fn parse_rules(text: &str) -> Result<Rules, NotationError> {
+    let (mode, extra_turn_on_sos) = match text {
+        "scoring" => (GameMode::Scoring, false),
+        "classic" => (GameMode::Scoring, true),
+        "simple" => (GameMode::Simple, false),
+        "simple+extra" => (GameMode::Simple, true),
+        _ => return Err(NotationError::InvalidRules(text.to_string())),
+    };
+    Ok(Rules { mode, extra_turn_on_sos })
+}
+
+/// Writes a move on a board `cols` wide, e.g. `S@c4`.
+pub fn format_move(pos: u32, value: CellValue, cols: u16) -> String {
+    format!("{}@{}", value.to_char(), format_square(pos, cols))
+}
+
+/// Reads a move written by `format_move` on a board of `rows` by `cols`.
+pub fn parse_move(text: &str, rows: u16, cols: u16) -> Result<(u32, CellValue), NotationError> {
+    let text = text.trim();
+    let error = || NotationError::InvalidMove(text.to_string());
+    let (letter, square) = text.split_once('@').ok_or_else(error)?;
+    let value = match letter.chars().collect::<Vec<_>>()[..] {
+        [c] => CellValue::from_char(c).filter(|&value| value != CellValue::Empty).ok_or_else(error)?,
+        _ => return Err(error()),
+    };
+    let pos = parse_square(square, rows, cols)?;
+    Ok((pos, value))
+}
+
+impl Game {
+    /// Writes a move on this board, e.g. `S@c4`.
+    pub fn format_move(&self, pos: u32, value: CellValue) -> String {
+        format_move(pos, value, self.get_cols())
+    }
+
+    /// Reads a move written by `format_move`. Only the notation is checked, not whether the
+    /// square is still empty.
+    pub fn parse_move(&self, text: &str) -> Result<(u32, CellValue), NotationError> {
+        parse_move(text, self.get_rows(), self.get_cols())
+    }
+
+    /// Writes the position in the notation described in this module. The move history is not
+    /// included.
+    pub fn to_notation(&self) -> String {
+        let rows: Vec<String> = self.board().rows().map(|row| {
+            let mut text = String::new();
+            let mut empty = 0;
+            for &cell in row {
+                if cell == CellValue::Empty {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    text += &empty.to_string();
+                    empty = 0;
+                }
+                text.push(cell.to_char());
+            }
+            if empty > 0 {
+                text += &empty.to_string();
+            }
+            text
+        }).collect();
+        let scores: Vec<String> = self.get_scores().iter().map(|score| score.to_string()).collect();
+
+        format!(
+            "{}x{} {} {} {} {}",
+            self.get_rows(),
+            self.get_cols(),
+            rows.join("/"),
+            self.get_current_turn() + 1,
+            scores.join(","),
+            format_rules(self.get_rules()),
+        )
+    }
+
+    /// Sets up a game from a position written by `to_notation`. The game starts without history.
+    pub fn from_notation(text: &str) -> Result<Game, NotationError> {
+        let fields: Vec<&str> = text.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(NotationError::MissingField);
+        }
+        if let Some(extra) = fields.get(5) {
+            return Err(NotationError::ExtraField(extra.to_string()));
+        }
+
+        let dimensions_error = || NotationError::InvalidDimensions(fields[0].to_string());
+        let (rows, cols) = fields[0].split_once('x').ok_or_else(dimensions_error)?;
+        let rows: u16 = parse_number(rows).ok_or_else(dimensions_error)?;
+        let cols: u16 = parse_number(cols).ok_or_else(dimensions_error)?;
+        let total = rows as u32 * cols as u32;
+        if total == 0 || total > MAX_CELLS {
+            return Err(dimensions_error());
+        }
+
+        let lines: Vec<&str> = fields[1].split('/').collect();
+        if lines.len() != rows as usize {
+            return Err(NotationError::InvalidRow(lines.len().min(rows as usize) + 1));
+        }
+        let mut cells = Vec::with_capacity(total as usize);
+        for (row, line) in lines.iter().enumerate() {
+            let start = cells.len();
+            let mut empty = String::new();
+            for c in line.chars().chain(std::iter::once(' ')) {
+                if c.is_ascii_digit() {
+                    empty.push(c);
+                    continue;
+                }
+                if !empty.is_empty() {
+                    let count: usize = empty.parse()
+                        .ok()
+                        .filter(|&count| count <= cols as usize)
+                        .ok_or(NotationError::InvalidRow(row + 1))?;
+                    cells.resize(cells.len() + count, CellValue::Empty);
+                    empty.clear();
+                }
+                if c != ' ' {
+                    cells.push(CellValue::from_char(c).ok_or(NotationError::InvalidRow(row + 1))?);
+                }
+            }
+            if cells.len() - start != cols as usize {
+                return Err(NotationError::InvalidRow(row + 1));
+            }
+        }
+
+        let scores = fields[3].split(',')
+            .map(parse_number::<u32>)
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| NotationError::InvalidScores(fields[3].to_string()))?;
+        if scores.is_empty() || scores.len() > u8::MAX as usize {
+            return Err(NotationError::InvalidScores(fields[3].to_string()));
+        }
+
+        let turn = parse_number::<u8>(fields[2])
+            .filter(|&turn| turn >= 1 && turn as usize <= scores.len())
+            .ok_or_else(|| NotationError::InvalidPlayer(fields[2].to_string()))?;
+
+        let rules = match fields.get(4) {
+            Some(rules) => parse_rules(rules)?,
+            None => Rules::default(),
+        };
+
+        let scored: u64 = scores.iter().map(|&score| score as u64).sum();
+        let game = Game::from_position(rows, cols, rules, cells, turn - 1, scores).map_err(|_| dimensions_error())?;
+        if game.get_sos().len() as u64 != scored {
+            return Err(NotationError::ScoreMismatch);
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares_round_trip() {
+        for (pos, cols, square) in [(0, 3, "a1"), (11, 4, "d3"), (25, 26, "z1"), (26, 27, "aa1"), (731, 1000, "abd1")] {
+            assert_eq!(format_square(pos, cols), square);
+            assert_eq!(parse_square(square, 30, cols), Ok(pos));
+        }
+        assert_eq!(parse_square("B2", 3, 3), Ok(4));
+    }
+
+    #[test]
+    fn rejects_invalid_squares() {
+        for square in ["", "a", "2", "a0", "d1", "a4", "a+3", "a-1", "a 1", "1a"] {
+            assert_eq!(parse_square(square, 3, 3), Err(NotationError::InvalidSquare(square.to_string())), "{}", square);
+        }
+    }
+
+    #[test]
+    fn moves_round_trip() {
+        assert_eq!(format_move(11, CellValue::O, 4), "O@d3");
+        assert_eq!(parse_move(" O@d3 ", 3, 4), Ok((11, CellValue::O)));
+        for text in ["S", "@a1", "Sa1", "SO@a1", ".@a1", "X@a1"] {
+            assert_eq!(parse_move(text, 3, 3), Err(NotationError::InvalidMove(text.to_string())), "{}", text);
+        }
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        for notation in [
+            "3x3 S1S/1O1/3 1 0,0 scoring",
+            "3x4 SOS1/4/1S2 2 1,0 classic",
+            "1x5 S4 3 0,0,0 simple",
+            "2x2 2/OO 1 0,0 simple+extra",
+        ] {
+            let game = Game::from_notation(notation).unwrap();
+            assert_eq!(game.to_notation(), notation);
+            assert_eq!(game.get_setup(), Some(notation));
+        }
+        let game = Game::from_notation("3x4 SOS./4/.S.. 2 1,0 classic").unwrap();
+        assert_eq!(game.to_notation(), "3x4 SOS1/4/1S2 2 1,0 classic");
+        assert_eq!(Game::from_notation("2x2 2/2 1 0,0").unwrap().get_rules(), Rules::default());
+    }
+
+    #[test]
+    fn undo_goes_back_to_the_setup() {
+        let mut game = Game::from_notation("3x3 S1S/1O1/3 1 0,0").unwrap();
+        game.make_move(1, CellValue::O).unwrap();
+        game.undo();
+        game.undo();
+        assert_eq!(game.to_notation(), "3x3 S1S/1O1/3 1 0,0 scoring");
+        // An empty board is a new game rather than a setup.
+        assert_eq!(Game::from_notation("2x2 2/2 1 0,0").unwrap().get_setup(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_positions() {
+        for (notation, error) in [
+            ("3x3 3/3/3", NotationError::MissingField),
+            ("3x3 3/3/3 1 0,0 classic extra", NotationError::ExtraField("extra".to_string())),
+            ("+3x3 3/3/3 1 0,0", NotationError::InvalidDimensions("+3x3".to_string())),
+            ("0x3 3/3/3 1 0,0", NotationError::InvalidDimensions("0x3".to_string())),
+            ("4096x4097 1 1 0,0", NotationError::InvalidDimensions("4096x4097".to_string())),
+            ("3x3 3/3 1 0,0", NotationError::InvalidRow(3)),
+            ("3x3 3/4/3 1 0,0", NotationError::InvalidRow(2)),
+            ("3x3 3/3/SX1 1 0,0", NotationError::InvalidRow(3)),
+            ("3x3 3/3/3 +1 0,0", NotationError::InvalidPlayer("+1".to_string())),
+            ("3x3 3/3/3 3 0,0", NotationError::InvalidPlayer("3".to_string())),
+            ("3x3 3/3/3 1 0,+0", NotationError::InvalidScores("0,+0".to_string())),
+            ("3x3 3/3/3 1 0,", NotationError::InvalidScores("0,".to_string())),
+            ("3x3 3/3/3 1 0,0 chess", NotationError::InvalidRules("chess".to_string())),
+            ("1x3 SOS 1 0,0", NotationError::ScoreMismatch),
+        ] {
+            assert_eq!(Game::from_notation(notation).map(|game| game.to_notation()), Err(error), "{}", notation);
+        }
+    }
+}
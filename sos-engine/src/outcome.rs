@@ -0,0 +1,42 @@
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WinReason {
+    /// The board is full and the winner completed the most SOS.
+    MostSos,
+    /// The winner completed the first SOS of a simple game.
+    FirstSos,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    /// The board is full and the leading players have the same score.
+    EqualScores,
+    /// The board of a simple game filled up without anyone completing an SOS.
+    NoSos,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Win { player: u8, reason: WinReason },
+    /// `players` are the players sharing the first place, everyone else lost.
+    Draw { players: Vec<u8>, reason: DrawReason },
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PlayerResult {
+    Win,
+    Lose,
+    Draw,
+}
+
+impl Outcome {
+    pub fn result_for(&self, player: u8) -> PlayerResult {
+        match self {
+            Outcome::Win { player: winner, .. } if *winner == player => PlayerResult::Win,
+            Outcome::Draw { players, .. } if players.contains(&player) => PlayerResult::Draw,
+            _ => PlayerResult::Lose,
+        }
+    }
+}
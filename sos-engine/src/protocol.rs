@@ -0,0 +1,336 @@
+//! The SOS engine protocol (SEP), a line based protocol in the style of UCI for chess.
+//!
+//! A front end or tournament manager starts the engine as a process and talks to it over stdin and
+//! stdout, one message per line. The front end sends commands:
+//!
+//! - `sep` starts the session. The engine replies with `id name <name>`, optionally
+//!   `id author <author>`, and finally `sepok`.
+//! - `isready` is answered with `readyok` once the engine has handled every earlier command.
+//! - `setoption name <name> value <value>` changes an engine specific option.
+//! - `newgame <rows> <cols> <players> [rules]` starts an empty board. The rules are named as in the
+//!   position notation and default to `scoring`.
+//! - `position <notation> [moves <move>...]` sets up a position in the notation of
+//!   `crate::notation`, optionally followed by moves played from it, e.g. `moves S@a1 O@b1`.
+//! - `go [movetime <ms>] [depth <n>] [nodes <n>]` asks for a move in the current position. The
+//!   engine may print `info <text>` lines while thinking and ends with `bestmove <move>`, or
+//!   `bestmove none` when there is nothing to play.
+//! - `quit` ends the session.
+//!
+//! The engine replies with `error <message>` to a command it cannot handle and otherwise ignores
+//! it. Unknown commands are errors too, so a new command never goes by unnoticed.
+//!
+//! ```text
+//! > sep
+//! < id name sos-sep
+//! < sepok
+//! > newgame 3 3 2 classic
+//! > position 3x3 SO1/3/3 1 0,0 classic
+//! > go movetime 500
+//! < info depth 7 score 999994 nodes 3276
+//! < bestmove S@c1
+//! ```
+
+use crate::game::{Game, GameError, MAX_CELLS};
+use crate::notation::{format_rules, parse_rules, NotationError};
+use crate::rules::Rules;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum ProtocolError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidArgument(String),
+    Notation(NotationError),
+    /// The move at this index after `moves`, counting from 0, cannot be played.
+    InvalidMove(usize, GameError),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::UnknownCommand(command) => write!(f, "unknown command `{}`", command),
+            ProtocolError::MissingArgument(name) => write!(f, "missing {}", name),
+            ProtocolError::InvalidArgument(text) => write!(f, "invalid argument `{}`", text),
+            ProtocolError::Notation(error) => error.fmt(f),
+            ProtocolError::InvalidMove(index, error) => write!(f, "move {}: {}", index + 1, error),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<NotationError> for ProtocolError {
+    fn from(error: NotationError) -> Self {
+        ProtocolError::Notation(error)
+    }
+}
+
+/// The limits of a `go` command, an engine picks its own for the ones left out.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct GoLimits {
+    pub movetime: Option<Duration>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+}
+
+/// A message from the front end to the engine.
+#[derive(PartialEq, Clone, Debug)]
+// A position is only sent once per move, so it is not worth a box.
+#[allow(clippy::large_enum_variant)]
+pub enum Command {
+    Sep,
+    IsReady,
+    SetOption { name: String, value: String },
+    NewGame { rows: u16, cols: u16, players: u8, rules: Rules },
+    /// The position with the moves after it already played.
+    Position(Game),
+    Go(GoLimits),
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, ProtocolError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = words.split_first() else {
+            return Err(ProtocolError::UnknownCommand(String::new()));
+        };
+        match command {
+            "sep" => Ok(Command::Sep),
+            "isready" => Ok(Command::IsReady),
+            "quit" => Ok(Command::Quit),
+            "setoption" => {
+                let (name, value) = match args {
+                    ["name", rest @ ..] => {
+                        let split = rest.iter().position(|&word| word == "value").unwrap_or(rest.len());
+                        (rest[..split].join(" "), rest.get(split + 1..).unwrap_or_default().join(" "))
+                    }
+                    _ => return Err(ProtocolError::MissingArgument("option name")),
+                };
+                if name.is_empty() {
+                    return Err(ProtocolError::MissingArgument("option name"));
+                }
+                Ok(Command::SetOption { name, value })
+            }
+            "newgame" => {
+                let number = |index: usize, name: &'static str| -> Result<u16, ProtocolError> {
+                    let text = args.get(index).ok_or(ProtocolError::MissingArgument(name))?;
+                    text.parse().ok().filter(|&value| value > 0).ok_or_else(|| ProtocolError::InvalidArgument(text.to_string()))
+                };
+                let rows = number(0, "rows")?;
+                let cols = number(1, "columns")?;
+                let players = number(2, "players")?;
+                if rows as u32 * cols as u32 > MAX_CELLS {
+                    return Err(ProtocolError::InvalidArgument(format!("{}x{}", rows, cols)));
+                }
+                let players = u8::try_from(players).map_err(|_| ProtocolError::InvalidArgument(players.to_string()))?;
+                let rules = match args.get(3) {
+                    Some(rules) => parse_rules(rules)?,
+                    None => Rules::default(),
+                };
+                Ok(Command::NewGame { rows, cols, players, rules })
+            }
+            "position" => {
+                let split = args.iter().position(|&word| word == "moves").unwrap_or(args.len());
+                if split == 0 {
+                    return Err(ProtocolError::MissingArgument("position"));
+                }
+                let mut game = Game::from_notation(&args[..split].join(" "))?;
+                for (index, text) in args.iter().skip(split + 1).enumerate() {
+                    let (pos, value) = game.parse_move(text)?;
+                    game.make_move(pos, value).map_err(|error| ProtocolError::InvalidMove(index, error))?;
+                }
+                Ok(Command::Position(game))
+            }
+            "go" => {
+                let mut limits = GoLimits::default();
+                for pair in args.chunks(2) {
+                    let [name, value] = pair else {
+                        return Err(ProtocolError::MissingArgument("limit value"));
+                    };
+                    let invalid = || ProtocolError::InvalidArgument(value.to_string());
+                    match *name {
+                        "movetime" => limits.movetime = Some(Duration::from_millis(value.parse().map_err(|_| invalid())?)),
+                        "depth" => limits.depth = Some(value.parse().map_err(|_| invalid())?),
+                        "nodes" => limits.nodes = Some(value.parse().map_err(|_| invalid())?),
+                        _ => return Err(ProtocolError::InvalidArgument(name.to_string())),
+                    }
+                }
+                Ok(Command::Go(limits))
+            }
+            _ => Err(ProtocolError::UnknownCommand(command.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Sep => f.write_str("sep"),
+            Command::IsReady => f.write_str("isready"),
+            Command::SetOption { name, value } => write!(f, "setoption name {} value {}", name, value),
+            Command::NewGame { rows, cols, players, rules } => {
+                write!(f, "newgame {} {} {} {}", rows, cols, players, format_rules(*rules))
+            }
+            Command::Position(game) => write!(f, "position {}", game.to_notation()),
+            Command::Go(limits) => {
+                f.write_str("go")?;
+                if let Some(movetime) = limits.movetime {
+                    write!(f, " movetime {}", movetime.as_millis())?;
+                }
+                if let Some(depth) = limits.depth {
+                    write!(f, " depth {}", depth)?;
+                }
+                if let Some(nodes) = limits.nodes {
+                    write!(f, " nodes {}", nodes)?;
+                }
+                Ok(())
+            }
+            Command::Quit => f.write_str("quit"),
+        }
+    }
+}
+
+/// A message from the engine to the front end.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Reply {
+    /// `id <key> <value>`, e.g. the name or the author of the engine.
+    Id { key: String, value: String },
+    SepOk,
+    ReadyOk,
+    /// Free text about the search, for people watching.
+    Info(String),
+    /// The move in notation, e.g. `S@c4`, `None` when there is nothing to play. The move is only
+    /// checked by the front end, which knows the board.
+    BestMove(Option<String>),
+    Error(String),
+}
+
+impl Reply {
+    pub fn parse(line: &str) -> Result<Reply, ProtocolError> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        match command {
+            "id" => {
+                let (key, value) = rest.split_once(' ').ok_or(ProtocolError::MissingArgument("id value"))?;
+                Ok(Reply::Id { key: key.to_string(), value: value.trim().to_string() })
+            }
+            "sepok" => Ok(Reply::SepOk),
+            "readyok" => Ok(Reply::ReadyOk),
+            "info" => Ok(Reply::Info(rest.to_string())),
+            "bestmove" => match rest {
+                "" => Err(ProtocolError::MissingArgument("move")),
+                "none" => Ok(Reply::BestMove(None)),
+                text => Ok(Reply::BestMove(Some(text.to_string()))),
+            },
+            "error" => Ok(Reply::Error(rest.to_string())),
+            _ => Err(ProtocolError::UnknownCommand(command.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reply::Id { key, value } => write!(f, "id {} {}", key, value),
+            Reply::SepOk => f.write_str("sepok"),
+            Reply::ReadyOk => f.write_str("readyok"),
+            Reply::Info(text) => write!(f, "info {}", text),
+            Reply::BestMove(Some(text)) => write!(f, "bestmove {}", text),
+            Reply::BestMove(None) => f.write_str("bestmove none"),
+            Reply::Error(message) => write!(f, "error {}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_survive_a_round_trip() {
+        let commands = [
+            Command::Sep,
+            Command::IsReady,
+            Command::SetOption { name: "Strategy".to_string(), value: "hard".to_string() },
+            Command::NewGame { rows: 5, cols: 7, players: 3, rules: Rules::classic() },
+            Command::Position(Game::from_notation("3x3 SO1/3/3 2 0,0 simple").unwrap()),
+            Command::Go(GoLimits::default()),
+            Command::Go(GoLimits { movetime: Some(Duration::from_millis(250)), depth: Some(4), nodes: Some(10_000) }),
+            Command::Quit,
+        ];
+        for command in commands {
+            assert_eq!(Command::parse(&command.to_string()).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn replies_survive_a_round_trip() {
+        let replies = [
+            Reply::Id { key: "name".to_string(), value: "sos sep".to_string() },
+            Reply::SepOk,
+            Reply::ReadyOk,
+            Reply::Info("depth 3 score 12".to_string()),
+            Reply::BestMove(Some("S@c4".to_string())),
+            Reply::BestMove(None),
+            Reply::Error("no position".to_string()),
+        ];
+        for reply in replies {
+            assert_eq!(Reply::parse(&reply.to_string()).unwrap(), reply);
+        }
+        assert!(matches!(Reply::parse("bestmove"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Reply::parse("id name"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Reply::parse("hello"), Err(ProtocolError::UnknownCommand(_))));
+    }
+
+    #[test]
+    fn position_plays_the_moves() {
+        let Command::Position(game) = Command::parse("position 3x3 3/3/3 1 0,0 moves S@a1 O@b1 S@c1").unwrap() else {
+            panic!("not a position");
+        };
+        assert_eq!(game.get_scores(), vec![1, 0]);
+        assert_eq!(game.get_history().len(), 3);
+    }
+
+    #[test]
+    fn position_reports_the_illegal_move() {
+        let error = Command::parse("position 3x3 3/3/3 1 0,0 moves S@a1 O@a1").unwrap_err();
+        assert!(matches!(error, ProtocolError::InvalidMove(1, _)), "{:?}", error);
+        assert!(matches!(Command::parse("position moves S@a1"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Command::parse("position 3x3 3/3/3"), Err(ProtocolError::Notation(_))));
+    }
+
+    #[test]
+    fn go_rejects_broken_limits() {
+        assert!(matches!(Command::parse("go depth"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Command::parse("go depth 3 nodes"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Command::parse("go mate 3"), Err(ProtocolError::InvalidArgument(name)) if name == "mate"));
+        assert!(matches!(Command::parse("go depth deep"), Err(ProtocolError::InvalidArgument(value)) if value == "deep"));
+    }
+
+    #[test]
+    fn setoption_takes_several_words() {
+        let command = Command::parse("setoption name Opening Book value my book.txt").unwrap();
+        assert_eq!(command, Command::SetOption { name: "Opening Book".to_string(), value: "my book.txt".to_string() });
+        let command = Command::parse("setoption name Clear Hash").unwrap();
+        assert_eq!(command, Command::SetOption { name: "Clear Hash".to_string(), value: String::new() });
+        assert!(matches!(Command::parse("setoption name value 3"), Err(ProtocolError::MissingArgument(_))));
+        assert!(matches!(Command::parse("setoption value 3"), Err(ProtocolError::MissingArgument(_))));
+    }
+
+    #[test]
+    fn newgame_rejects_bad_sizes() {
+        for line in ["newgame 0 3 2", "newgame 3 0 2", "newgame 3 3 0", "newgame 70000 3 2", "newgame 3 3 300", "newgame 5000 5000 2"] {
+            assert!(matches!(Command::parse(line), Err(ProtocolError::InvalidArgument(_))), "{}", line);
+        }
+        assert!(matches!(Command::parse("newgame 3 3"), Err(ProtocolError::MissingArgument("players"))));
+        assert!(matches!(Command::parse("newgame 3 3 2 chess"), Err(ProtocolError::Notation(_))));
+    }
+
+    #[test]
+    fn unknown_and_empty_commands_are_errors() {
+        assert!(matches!(Command::parse("uci"), Err(ProtocolError::UnknownCommand(command)) if command == "uci"));
+        assert!(matches!(Command::parse("   "), Err(ProtocolError::UnknownCommand(_))));
+    }
+}
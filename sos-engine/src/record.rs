@@ -0,0 +1,360 @@
+//! The `.sos` game record format, a plain text file in the spirit of PGN.
+//!
+//! A record starts with header lines of the form `[Key "value"]`, followed by a blank line and
+//! the numbered moves:
+//!
+//! ```text
+//! [Rows "3"]
+//! [Cols "3"]
+//! [Players "2"]
+//! [Player1 "Alice"]
+//! [Player2 "Bob"]
+//! [Rules "classic"]
+//! [Date "2024.05.01"]
+//! [Result "*"]
+//!
+//! 1. S@a1
+//! 2. O@b1
+//! 3. S@c1 {a1 b1 c1}
+//! ```
+//!
+//! - `Rows`, `Cols` and `Players` are required, everything else is optional.
+//! - `PlayerN` names the Nth player, counting from 1.
+//! - `Rules` uses the rule names of the position notation and defaults to `scoring`.
+//! - `Setup` is the position the moves start from, in the position notation. It is left out for
+//!   games from an empty board and has to agree with `Rows`, `Cols`, `Players` and `Rules`.
+//! - `Date` is free text, `YYYY.MM.DD` by convention.
+//! - `Result` lists the final scores of all players joined by `-`, or `*` for an unfinished game.
+//!   It is written for readers and recomputed from the moves when a record is read.
+//!
+//! Moves use the notation `S@c4` and may be numbered. A move that completes SOS is followed by
+//! one `{...}` annotation per SOS listing its three squares. Annotations are checked against
+//! the board when the record is read, but may be left out.
+//! Other header keys are kept as they are. In header values `\\`, `\"`, `\n` and `\r` stand
+//! for a backslash, a quote, a line feed and a carriage return.
+
+use crate::game::{Game, GameError, MAX_CELLS};
+use crate::history::MoveRecord;
+use crate::notation::{format_move, format_rules, format_square, parse_move, parse_rules, parse_square, NotationError};
+use crate::rules::Rules;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum RecordError {
+    /// The required header is missing or not a positive number.
+    MissingHeader(&'static str),
+    /// The line, counting from 1, is not a valid header.
+    InvalidHeader(usize),
+    Notation(NotationError),
+    /// The move at this index, counting from 0, cannot be played.
+    InvalidMove(usize, GameError),
+    /// The move at this index, counting from 0, does not complete the SOS it is annotated with.
+    SosMismatch(usize),
+    /// The setup position does not match the size, the players or the rules.
+    SetupMismatch,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::MissingHeader(key) => write!(f, "missing or invalid header `{}`", key),
+            RecordError::InvalidHeader(line) => write!(f, "line {}: invalid header", line),
+            RecordError::Notation(error) => error.fmt(f),
+            RecordError::InvalidMove(index, error) => write!(f, "move {}: {}", index + 1, error),
+            RecordError::SosMismatch(index) => write!(f, "move {}: the SOS do not match the board", index + 1),
+            RecordError::SetupMismatch => f.write_str("the setup does not match the other headers"),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl From<NotationError> for RecordError {
+    fn from(error: NotationError) -> Self {
+        RecordError::Notation(error)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct GameRecord {
+    pub rows: u16,
+    pub cols: u16,
+    pub rules: Rules,
+    /// The position the moves start from, `None` for an empty board.
+    pub setup: Option<String>,
+    /// One name per player, the number of names is the number of players.
+    pub names: Vec<String>,
+    pub date: Option<String>,
+    /// Header keys that are not part of the format, in the order they were read.
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<MoveRecord>,
+}
+
+impl GameRecord {
+    /// Records the moves currently on the board of `game`.
+    pub fn from_game(game: &Game, names: Vec<String>, date: Option<String>) -> Self {
+        Self {
+            rows: game.get_rows(),
+            cols: game.get_cols(),
+            rules: game.get_rules(),
+            setup: game.get_setup().map(str::to_string),
+            names,
+            date,
+            tags: vec![],
+            moves: game.get_history().to_vec(),
+        }
+    }
+
+    /// Replays the record, checking every move. The SOS of a move are only checked when it
+    /// lists any, the order of the SOS and the direction they are written in do not matter.
+    pub fn to_game(&self) -> Result<Game, RecordError> {
+        let mut game = match &self.setup {
+            Some(setup) => Game::from_notation(setup)?,
+            None => Game::new(self.rows, self.cols, self.names.len() as u8, self.rules)
+                .map_err(|_| NotationError::InvalidDimensions(format!("{}x{}", self.rows, self.cols)))?,
+        };
+        let board = (game.get_rows(), game.get_cols(), game.get_num_of_players() as usize, game.get_rules());
+        if board != (self.rows, self.cols, self.names.len(), self.rules) {
+            return Err(RecordError::SetupMismatch);
+        }
+        for (index, record) in self.moves.iter().enumerate() {
+            let response = game.update(game.get_current_turn(), record.pos, record.value)
+                .map_err(|error| RecordError::InvalidMove(index, error))?;
+            if !record.sos.is_empty() && GameRecord::normalize(&response.new_sos) != GameRecord::normalize(&record.sos) {
+                return Err(RecordError::SosMismatch(index));
+            }
+        }
+        Ok(game)
+    }
+
+    fn normalize(sos: &[(u32, u32, u32)]) -> Vec<(u32, u32, u32)> {
+        let mut sos: Vec<(u32, u32, u32)> = sos.iter()
+            .map(|&(a, b, c)| if a > c { (c, b, a) } else { (a, b, c) })
+            .collect();
+        sos.sort();
+        sos
+    }
+
+    pub fn write(&self) -> String {
+        let mut text = String::new();
+        let mut header = |key: &str, value: &str| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r");
+            text += &format!("[{} \"{}\"]\n", key, value);
+        };
+        header("Rows", &self.rows.to_string());
+        header("Cols", &self.cols.to_string());
+        header("Players", &self.names.len().to_string());
+        for (player, name) in self.names.iter().enumerate() {
+            header(&format!("Player{}", player + 1), name);
+        }
+        header("Rules", format_rules(self.rules));
+        if let Some(setup) = &self.setup {
+            header("Setup", setup);
+        }
+        if let Some(date) = &self.date {
+            header("Date", date);
+        }
+        let result = match self.to_game() {
+            Ok(game) if game.is_game_over() => {
+                game.get_scores().iter().map(|score| score.to_string()).collect::<Vec<_>>().join("-")
+            }
+            _ => "*".to_string(),
+        };
+        header("Result", &result);
+        for (key, value) in &self.tags {
+            header(key, value);
+        }
+
+        text.push('\n');
+        for (index, record) in self.moves.iter().enumerate() {
+            text += &format!("{}. {}", index + 1, format_move(record.pos, record.value, self.cols));
+            for &(a, b, c) in &record.sos {
+                text += &format!(
+                    " {{{} {} {}}}",
+                    format_square(a, self.cols),
+                    format_square(b, self.cols),
+                    format_square(c, self.cols),
+                );
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<GameRecord, RecordError> {
+        let mut headers: Vec<(String, String)> = vec![];
+        let mut body = String::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('[') && body.trim().is_empty() {
+                headers.push(GameRecord::parse_header(line).ok_or(RecordError::InvalidHeader(index + 1))?);
+            } else {
+                body += line;
+                body.push('\n');
+            }
+        }
+
+        let header = |key: &'static str| {
+            headers.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+        };
+        let number = |key: &'static str| -> Result<u16, RecordError> {
+            let value = header(key).ok_or(RecordError::MissingHeader(key))?;
+            value.parse().ok().filter(|&value| value > 0).ok_or(RecordError::MissingHeader(key))
+        };
+        let rows = number("Rows")?;
+        let cols = number("Cols")?;
+        let players = number("Players")?.min(u8::MAX as u16) as usize;
+        if rows as u32 * cols as u32 > MAX_CELLS {
+            return Err(NotationError::InvalidDimensions(format!("{}x{}", rows, cols)).into());
+        }
+        let rules = match header("Rules") {
+            Some(rules) => parse_rules(rules)?,
+            None => Rules::default(),
+        };
+        let names = (1..=players)
+            .map(|player| {
+                headers.iter()
+                    .find(|(key, _)| *key == format!("Player{}", player))
+                    .map(|(_, name)| name.clone())
+                    .unwrap_or_else(|| format!("Player {}", player))
+            })
+            .collect();
+        let setup = header("Setup").map(str::to_string);
+        let date = header("Date").map(str::to_string);
+        let tags = headers.iter()
+            .filter(|(key, _)| !GameRecord::is_known_header(key, players))
+            .cloned()
+            .collect();
+
+        let moves = GameRecord::parse_moves(&body, rows, cols)?;
+        let record = GameRecord { rows, cols, rules, setup, names, date, tags, moves };
+        // Fill in the players and the unannotated SOS from the replay.
+        let game = record.to_game()?;
+        Ok(GameRecord { moves: game.get_history().to_vec(), ..record })
+    }
+
+    fn is_known_header(key: &str, players: usize) -> bool {
+        match key {
+            "Rows" | "Cols" | "Players" | "Rules" | "Setup" | "Date" | "Result" => true,
+            _ => key.strip_prefix("Player")
+                .and_then(|player| player.parse::<usize>().ok())
+                .is_some_and(|player| player >= 1 && player <= players),
+        }
+    }
+
+    fn parse_header(line: &str) -> Option<(String, String)> {
+        let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+        let (key, value) = inner.split_once(' ')?;
+        let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            unescaped.push(match c {
+                '\\' => match chars.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    c => c,
+                },
+                c => c,
+            });
+        }
+        Some((key.to_string(), unescaped))
+    }
+
+    // Moves are read with the SOS from their annotations, the player is filled in by the replay.
+    fn parse_moves(body: &str, rows: u16, cols: u16) -> Result<Vec<MoveRecord>, RecordError> {
+        let mut moves: Vec<MoveRecord> = vec![];
+        let mut rest = body;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+            if let Some(annotation) = rest.strip_prefix('{') {
+                let end = annotation.find('}').ok_or_else(|| NotationError::InvalidMove(rest.to_string()))?;
+                let squares = annotation[..end].split_whitespace()
+                    .map(|square| parse_square(square, rows, cols))
+                    .collect::<Result<Vec<u32>, _>>()?;
+                let record = moves.last_mut().ok_or_else(|| NotationError::InvalidMove(rest.to_string()))?;
+                match squares[..] {
+                    [a, b, c] => record.sos.push((a, b, c)),
+                    _ => return Err(NotationError::InvalidMove(annotation[..end].to_string()).into()),
+                }
+                rest = &annotation[end + 1..];
+                continue;
+            }
+
+            let end = rest.find(|c: char| c.is_whitespace() || c == '{').unwrap_or(rest.len());
+            let token = &rest[..end];
+            rest = &rest[end..];
+            if token.ends_with('.') && token[..token.len() - 1].chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let (pos, value) = parse_move(token, rows, cols)?;
+            moves.push(MoveRecord { player: 0, pos, value, sos: vec![] });
+        }
+        Ok(moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellValue;
+
+    fn names() -> Vec<String> {
+        vec!["Alice".to_string(), "Bob".to_string()]
+    }
+
+    #[test]
+    fn games_from_an_empty_board_round_trip() {
+        let mut game = Game::new(3, 3, 2, Rules::classic()).unwrap();
+        for (pos, value) in [(0, CellValue::S), (1, CellValue::O), (2, CellValue::S), (4, CellValue::O)] {
+            game.make_move(pos, value).unwrap();
+        }
+        let record = GameRecord::from_game(&game, names(), Some("2024.05.01".to_string()));
+        let text = record.write();
+        assert!(text.contains("3. S@c1 {a1 b1 c1}\n"));
+        assert!(!text.contains("[Setup"));
+        let parsed = GameRecord::parse(&text).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.to_game().unwrap(), game);
+    }
+
+    #[test]
+    fn games_from_a_position_round_trip() {
+        let mut game = Game::from_notation("3x3 S1S/1O1/3 1 0,0").unwrap();
+        game.make_move(1, CellValue::O).unwrap();
+        let record = GameRecord::from_game(&game, names(), None);
+        let text = record.write();
+        assert!(text.contains("[Setup \"3x3 S1S/1O1/3 1 0,0 scoring\"]\n"));
+        let parsed = GameRecord::parse(&text).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.to_game().unwrap().to_notation(), game.to_notation());
+    }
+
+    #[test]
+    fn escapes_header_values() {
+        let game = Game::new(3, 3, 2, Rules::default()).unwrap();
+        let mut record = GameRecord::from_game(&game, vec!["Al \"the\" \\ one".to_string(), "Bob\n[Rows \"9\"]\r".to_string()], None);
+        record.tags.push(("Event".to_string(), "Club\nnight".to_string()));
+        let text = record.write();
+        assert!(text.contains("[Player2 \"Bob\\n[Rows \\\"9\\\"]\\r\"]\n"));
+        assert_eq!(text.lines().filter(|line| line.starts_with("[Rows")).count(), 1);
+        assert_eq!(GameRecord::parse(&text).unwrap(), record);
+    }
+
+    #[test]
+    fn rejects_malformed_records() {
+        let header = "[Rows \"1\"]\n[Cols \"3\"]\n[Players \"2\"]\n";
+        assert!(matches!(GameRecord::parse("[Cols \"3\"]\n[Players \"2\"]\n"), Err(RecordError::MissingHeader("Rows"))));
+        assert!(matches!(GameRecord::parse("[Rows \"0\"]\n[Cols \"3\"]\n[Players \"2\"]\n"), Err(RecordError::MissingHeader("Rows"))));
+        assert!(matches!(GameRecord::parse(&format!("{}[Date 2024]\n", header)), Err(RecordError::InvalidHeader(4))));
+        assert!(matches!(GameRecord::parse(&format!("{}[Rules \"chess\"]\n", header)), Err(RecordError::Notation(NotationError::InvalidRules(_)))));
+        assert!(matches!(GameRecord::parse(&format!("{}\n1. S@d1\n", header)), Err(RecordError::Notation(NotationError::InvalidSquare(_)))));
+        assert!(matches!(GameRecord::parse(&format!("{}\n1. X@a1\n", header)), Err(RecordError::Notation(NotationError::InvalidMove(_)))));
+        assert!(matches!(GameRecord::parse(&format!("{}\n1. S@a1 2. O@a1\n", header)), Err(RecordError::InvalidMove(1, GameError::PositionAlreadyOccupied))));
+        assert!(matches!(GameRecord::parse(&format!("{}\n1. S@a1 {{a1 b1 c1}}\n", header)), Err(RecordError::SosMismatch(0))));
+        assert!(matches!(GameRecord::parse(&format!("{}\n1. S@a1 {{a1 b1}}\n", header)), Err(RecordError::Notation(NotationError::InvalidMove(_)))));
+        assert!(matches!(GameRecord::parse(&format!("{}[Setup \"1x4 S3 1 0,0\"]\n", header)), Err(RecordError::SetupMismatch)));
+        assert!(matches!(GameRecord::parse(&format!("{}[Setup \"1x3 S2 1 0,0,0\"]\n", header)), Err(RecordError::SetupMismatch)));
+        assert!(matches!(GameRecord::parse(&format!("{}[Setup \"1x3 S2\"]\n", header)), Err(RecordError::Notation(NotationError::MissingField))));
+    }
+}
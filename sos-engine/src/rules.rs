@@ -0,0 +1,33 @@
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMode {
+    /// The game runs until the board is full and the player with the most SOS wins.
+    #[default]
+    Scoring,
+    /// Sudden death: the first player to complete an SOS wins immediately.
+    Simple,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    pub mode: GameMode,
+    /// A player who completes at least one SOS moves again instead of passing the turn.
+    pub extra_turn_on_sos: bool,
+}
+
+impl Rules {
+    pub fn classic() -> Self {
+        Self {
+            mode: GameMode::Scoring,
+            extra_turn_on_sos: true,
+        }
+    }
+
+    pub fn simple() -> Self {
+        Self {
+            mode: GameMode::Simple,
+            extra_turn_on_sos: false,
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Versioned JSON schema used when a `Game` is serialized with serde.
+//!
+//! A game is stored as its setup and move list rather than its internal state, so a saved game
+//! is validated by replaying every move when it is loaded. Version 2 looks like this:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "rows": 3,
+//!   "cols": 3,
+//!   "players": 2,
+//!   "rules": { "mode": "Scoring", "extra_turn_on_sos": true },
+//!   "setup": null,
+//!   "moves": [
+//!     { "player": 0, "pos": 0, "value": "S", "sos": [] },
+//!     { "player": 1, "pos": 1, "value": "O", "sos": [] },
+//!     { "player": 0, "pos": 2, "value": "S", "sos": [[0, 1, 2]] }
+//!   ],
+//!   "played": 3
+//! }
+//! ```
+//!
+//! - `version`: the schema version, loading a newer version fails.
+//! - `rows`, `cols`, `players`: the arguments of `Game::new`.
+//! - `rules.mode`: `"Scoring"` or `"Simple"`.
+//! - `setup`: the position the moves start from in the notation of `notation`, e.g.
+//!   `"3x3 S1S/1O1/3 1 0,0 classic"`, or `null` for an empty board. It has to agree with the
+//!   fields above.
+//! - `moves`: every move in the order it was played. `pos` is `row * cols + col`, `value` is
+//!   `"S"` or `"O"` and `sos` lists the cells of each SOS the move completed.
+//! - `played`: how many of the moves are on the board, the rest were taken back and can be
+//!   redone.
+//!
+//! `CellValue`, `MoveRecord`, `UpdateResponse` and `Outcome` use the plain serde layout shown above.
+//! Fields may only be added under a new version number. Version 1 is version 2 without `setup`
+//! and is still loaded as a game from an empty board.
+
+use crate::game::{Game, GameError};
+use crate::history::MoveRecord;
+use crate::rules::Rules;
+use serde::{Deserialize, Serialize};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub rows: u16,
+    pub cols: u16,
+    pub players: u8,
+    pub rules: Rules,
+    #[serde(default)]
+    pub setup: Option<String>,
+    pub moves: Vec<MoveRecord>,
+    pub played: usize,
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    UnsupportedVersion(u32),
+    InvalidBoard,
+    /// The setup is not a position or does not match the board, the players or the rules.
+    InvalidSetup,
+    /// The move at this index of `moves` cannot be played.
+    InvalidMove(usize, GameError),
+    /// The move at this index of `moves` does not complete the SOS it lists.
+    SosMismatch(usize),
+    InvalidPlayed,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::UnsupportedVersion(version) => write!(f, "unsupported schema version {}", version),
+            SchemaError::InvalidBoard => f.write_str("the board needs one to `MAX_CELLS` cells and at least one player"),
+            SchemaError::InvalidSetup => f.write_str("the setup does not match the game"),
+            SchemaError::InvalidMove(index, error) => write!(f, "move {}: {}", index, error),
+            SchemaError::SosMismatch(index) => write!(f, "move {}: the SOS do not match the board", index),
+            SchemaError::InvalidPlayed => f.write_str("more moves played than recorded"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<Game> for SavedGame {
+    fn from(game: Game) -> Self {
+        let mut moves = game.get_history().to_vec();
+        moves.extend_from_slice(game.get_redo_moves());
+        Self {
+            version: SCHEMA_VERSION,
+            rows: game.get_rows(),
+            cols: game.get_cols(),
+            players: game.get_num_of_players(),
+            rules: game.get_rules(),
+            setup: game.get_setup().map(str::to_string),
+            played: game.get_history().len(),
+            moves,
+        }
+    }
+}
+
+impl TryFrom<SavedGame> for Game {
+    type Error = SchemaError;
+
+    fn try_from(saved: SavedGame) -> Result<Self, Self::Error> {
+        if saved.version == 0 || saved.version > SCHEMA_VERSION {
+            return Err(SchemaError::UnsupportedVersion(saved.version));
+        }
+        if saved.players == 0 {
+            return Err(SchemaError::InvalidBoard);
+        }
+        if saved.played > saved.moves.len() {
+            return Err(SchemaError::InvalidPlayed);
+        }
+        let mut game = Game::new(saved.rows, saved.cols, saved.players, saved.rules)
+            .map_err(|_| SchemaError::InvalidBoard)?;
+        if let Some(setup) = &saved.setup {
+            game = Game::from_notation(setup).map_err(|_| SchemaError::InvalidSetup)?;
+            let board = (game.get_rows(), game.get_cols(), game.get_num_of_players(), game.get_rules());
+            if board != (saved.rows, saved.cols, saved.players, saved.rules) {
+                return Err(SchemaError::InvalidSetup);
+            }
+        }
+        for (index, record) in saved.moves.iter().enumerate() {
+            let response = game.update(record.player, record.pos, record.value)
+                .map_err(|error| SchemaError::InvalidMove(index, error))?;
+            if response.new_sos != record.sos {
+                return Err(SchemaError::SosMismatch(index));
+            }
+        }
+        for _ in saved.played..saved.moves.len() {
+            game.undo();
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::CellValue;
+
+    fn round_trip(game: &Game) -> Game {
+        let json = serde_json::to_string(game).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn games_from_an_empty_board_round_trip() {
+        let mut game = Game::new(3, 4, 2, Rules::classic()).unwrap();
+        for (pos, value) in [(0, CellValue::S), (1, CellValue::O), (2, CellValue::S), (5, CellValue::O)] {
+            game.make_move(pos, value).unwrap();
+        }
+        game.undo();
+        let loaded = round_trip(&game);
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.get_redo_moves().len(), 1);
+        assert_eq!(SavedGame::from(game).setup, None);
+    }
+
+    #[test]
+    fn games_from_a_position_round_trip() {
+        let mut game = Game::from_notation("3x3 S1S/1O1/3 1 0,0").unwrap();
+        game.make_move(1, CellValue::O).unwrap();
+        let loaded = round_trip(&game);
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.to_notation(), "3x3 SOS/1O1/3 2 1,0 scoring");
+        assert_eq!(loaded.get_setup(), Some("3x3 S1S/1O1/3 1 0,0 scoring"));
+    }
+
+    #[test]
+    fn loads_version_1() {
+        let json = r#"{"version":1,"rows":1,"cols":3,"players":2,"rules":{"mode":"Scoring","extra_turn_on_sos":false},
+            "moves":[{"player":0,"pos":0,"value":"S","sos":[]}],"played":1}"#;
+        let game: Game = serde_json::from_str(json).unwrap();
+        assert_eq!(game.to_notation(), "1x3 S2 2 0,0 scoring");
+    }
+
+    #[test]
+    fn rejects_a_setup_that_does_not_match() {
+        let game = Game::from_notation("3x3 S1S/1O1/3 1 0,0").unwrap();
+        for setup in ["3x3 S1S/1O1/3 1 0,0,0", "3x3 S1S/1O1/3 1 0,0 classic", "3x3 S1S/1O1", "1x9 S1S1O4 1 0,0"] {
+            let saved = SavedGame { setup: Some(setup.to_string()), ..SavedGame::from(game.clone()) };
+            assert!(matches!(Game::try_from(saved), Err(SchemaError::InvalidSetup)), "{}", setup);
+        }
+        let saved = SavedGame { version: SCHEMA_VERSION + 1, ..SavedGame::from(game) };
+        assert!(matches!(Game::try_from(saved), Err(SchemaError::UnsupportedVersion(_))));
+    }
+}
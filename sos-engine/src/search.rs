@@ -0,0 +1,389 @@
+use crate::cell::CellValue;
+use crate::game::Game;
+use crate::outcome::PlayerResult;
+use crate::rules::GameMode;
+use crate::strategy::{move_rng, Strategy};
+use crate::transposition::TranspositionTable;
+use crate::zobrist;
+use rand::seq::SliceRandom;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+pub(crate) const INFINITY: i32 = i32::MAX / 2;
+const WIN: i32 = 1_000_000;
+// Entries of the table a search starts with, about 2 MB.
+const TABLE_ENTRIES: usize = 1 << 16;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SearchLimits {
+    /// Deepest iteration of the iterative deepening, in moves.
+    pub depth: u8,
+    /// Maximum number of positions visited.
+    pub nodes: u64,
+    /// Maximum thinking time. Ignored on wasm, where `std` has no clock. The move found in time
+    /// depends on the speed of the machine, so only the other limits give reproducible games.
+    pub time: Option<Duration>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SearchResult {
+    pub pos: u32,
+    pub value: CellValue,
+    /// Evaluation for the player to move, scores beyond `WIN` magnitude mean a decided game.
+    pub score: i32,
+    /// Deepest fully searched iteration, 0 when the limits cut the first one short and the move
+    /// completing the most SOS was taken.
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+/// How a stored score relates to the real value of the position.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Bound {
+    Exact,
+    /// The real value is at least the score.
+    Lower,
+    /// The real value is at most the score.
+    Upper,
+}
+
+impl Bound {
+    /// The bound of the best score found by searching a position with the window `alpha..beta`.
+    pub(crate) fn of(score: i32, alpha: i32, beta: i32) -> Bound {
+        if score <= alpha {
+            Bound::Upper
+        } else if score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        }
+    }
+}
+
+/// What `Search` remembers about a position in its transposition table.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SearchEntry {
+    /// Remaining depth the position was searched to.
+    pub depth: u8,
+    /// Evaluation for the player to move. Decided games are not stored, their scores depend on
+    /// the distance from the root.
+    pub score: i32,
+    pub bound: Bound,
+    /// The move that was best or caused the cutoff, tried first the next time.
+    pub best: Option<(u32, CellValue)>,
+}
+
+impl SearchEntry {
+    /// The stored score, if it settles a search of the position with the window `alpha..beta`.
+    pub(crate) fn cutoff(&self, alpha: i32, beta: i32) -> Option<i32> {
+        match self.bound {
+            Bound::Exact => Some(self.score),
+            Bound::Lower if self.score >= beta => Some(self.score),
+            Bound::Upper if self.score <= alpha => Some(self.score),
+            _ => None,
+        }
+    }
+}
+
+/// A transposition table for `Search`, shared with `Search::with_table`.
+pub type SearchTable = Rc<RefCell<TranspositionTable<SearchEntry>>>;
+
+/// Negamax search with alpha-beta pruning.
+///
+/// The player to move at the root plays against all other players together, so the search also
+/// works with more than two players. Extra turns are handled by only negating the score when the
+/// move passes to the other side.
+pub struct Search {
+    limits: SearchLimits,
+    seed: u64,
+    root_player: u8,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    killers: Vec<Option<(u32, CellValue)>>,
+    table: SearchTable,
+    root_key: u64,
+}
+
+impl Search {
+    /// The seed decides between equally good moves.
+    pub fn new(limits: SearchLimits, seed: u64) -> Self {
+        Self {
+            limits,
+            seed,
+            root_player: 0,
+            nodes: 0,
+            deadline: None,
+            aborted: false,
+            killers: vec![],
+            table: Rc::new(RefCell::new(TranspositionTable::new(TABLE_ENTRIES))),
+            root_key: 0,
+        }
+    }
+
+    /// Uses `table` instead of a table of its own, e.g. one kept for the whole game or shared
+    /// with another search. The entries left by earlier searches can change which of equally
+    /// good moves is found, so the seed alone no longer decides it.
+    pub fn with_table(self, table: SearchTable) -> Self {
+        Self { table, ..self }
+    }
+
+    pub fn best_move(&mut self, game: &Game) -> Option<SearchResult> {
+        if game.is_game_over() {
+            return None;
+        }
+        let mut game = game.clone();
+        self.root_player = game.get_current_turn();
+        // With more than two players the evaluations depend on whom the search is run for.
+        self.root_key = zobrist::root_key(self.root_player);
+        self.nodes = 0;
+        self.aborted = false;
+        self.deadline = start_clock(self.limits.time);
+        self.killers = vec![None; self.limits.depth as usize + 1];
+
+        // Shuffle first so that equally good moves are not always taken from the top left corner.
+        let mut moves = Search::generate_moves(&game);
+        moves.shuffle(&mut move_rng(self.seed, &game));
+        moves.sort_by_key(|&(_, _, gain)| std::cmp::Reverse(gain));
+
+        // Until an iteration is complete the move completing the most SOS stands in, so a running
+        // game always gets a move.
+        let (pos, value, gain) = moves[0];
+        let mut best = SearchResult { pos, value, score: game.margin(self.root_player) + gain as i32, depth: 0, nodes: 0 };
+        for depth in 1..=self.limits.depth {
+            let mut alpha = -INFINITY;
+            let mut iteration_best = None;
+            for (index, &(pos, value, _)) in moves.iter().enumerate() {
+                let score = self.search_move(&mut game, pos, value, depth - 1, 1, alpha, INFINITY);
+                if self.aborted {
+                    break;
+                }
+                if iteration_best.is_none() || score > alpha {
+                    alpha = score;
+                    iteration_best = Some((index, score));
+                }
+            }
+            // A partially searched iteration is not trusted, the previous one is kept instead.
+            if self.aborted {
+                break;
+            }
+            if let Some((index, score)) = iteration_best {
+                let (pos, value, _) = moves[index];
+                best = SearchResult { pos, value, score, depth, nodes: self.nodes };
+                moves[..=index].rotate_right(1);
+                // The game is decided, searching deeper cannot change the move.
+                if score.abs() >= WIN / 2 {
+                    break;
+                }
+            }
+        }
+        Some(SearchResult { nodes: self.nodes, ..best })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_move(&mut self, game: &mut Game, pos: u32, value: CellValue, depth: u8, ply: u8, alpha: i32, beta: i32) -> i32 {
+        score_move(game, self.root_player, (pos, value), alpha, beta, |game, alpha, beta| self.negamax(game, depth, ply, alpha, beta))
+    }
+
+    fn negamax(&mut self, game: &mut Game, depth: u8, ply: u8, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes >= self.limits.nodes || (self.nodes & 1023 == 0 && self.out_of_time()) {
+            self.aborted = true;
+            return 0;
+        }
+
+        let sign = if game.get_current_turn() == self.root_player { 1 } else { -1 };
+        if game.is_game_over() {
+            return sign * self.terminal_score(game, ply);
+        }
+
+        let mut moves = Search::generate_moves(game);
+        let best_gain = moves.iter().map(|&(_, _, gain)| gain).max().unwrap_or(0) as i32;
+        if depth == 0 {
+            // The player to move is assumed to cash in the best SOS on offer.
+            if best_gain > 0 && game.get_rules().mode == GameMode::Simple {
+                return WIN - ply as i32 - 1;
+            }
+            return sign * game.margin(self.root_player) + best_gain;
+        }
+
+        let key = game.get_hash() ^ self.root_key;
+        let entry = self.table.borrow().get(key);
+        if let Some(score) = entry.filter(|entry| entry.depth >= depth).and_then(|entry| entry.cutoff(alpha, beta)) {
+            return score;
+        }
+        order_moves(game, &mut moves, entry.and_then(|entry| entry.best), self.killers[ply as usize]);
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for (pos, value, gain) in moves {
+            let score = self.search_move(game, pos, value, depth - 1, ply + 1, alpha, beta);
+            if self.aborted {
+                return 0;
+            }
+            if score > best {
+                best = score;
+                best_move = Some((pos, value));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                if gain == 0 {
+                    self.killers[ply as usize] = Some((pos, value));
+                }
+                break;
+            }
+        }
+
+        if best.abs() < WIN / 2 {
+            let bound = Bound::of(best, original_alpha, beta);
+            self.table.borrow_mut().insert(key, SearchEntry { depth, score: best, bound, best: best_move });
+        }
+        best
+    }
+
+    // Every legal move with the number of SOS it completes.
+    pub(crate) fn generate_moves(game: &Game) -> Vec<(u32, CellValue, u16)> {
+        let mut moves = Vec::with_capacity(2 * game.board().get_num_of_cells() as usize);
+        for i in game.board().empty_cells() {
+            let threat = game.get_threat(i);
+            moves.push((i, CellValue::S, threat.s as u16));
+            moves.push((i, CellValue::O, threat.o as u16));
+        }
+        moves
+    }
+
+    // Faster wins and slower losses are preferred by taking the distance from the root into account.
+    fn terminal_score(&self, game: &Game, ply: u8) -> i32 {
+        let margin = game.margin(self.root_player);
+        match game.outcome().map(|outcome| outcome.result_for(self.root_player)) {
+            Some(PlayerResult::Win) => WIN - ply as i32 + margin,
+            Some(PlayerResult::Lose) => -WIN + ply as i32 + margin,
+            _ => margin,
+        }
+    }
+
+    fn out_of_time(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => now().is_some_and(|now| now >= deadline),
+            None => false,
+        }
+    }
+}
+
+/// Plays `mv` for the player to move and scores the position after it for them with `score`, as
+/// `Search` and `Solver` do with their negamax. `score` gets the window of the side to move then,
+/// which after an extra turn is still the same side, so the score is only negated when the move
+/// passes to the other side. Illegal moves score `-INFINITY`.
+pub(crate) fn score_move(
+    game: &mut Game,
+    root_player: u8,
+    (pos, value): (u32, CellValue),
+    alpha: i32,
+    beta: i32,
+    score: impl FnOnce(&mut Game, i32, i32) -> i32,
+) -> i32 {
+    let mover_is_root = game.get_current_turn() == root_player;
+    if game.update(game.get_current_turn(), pos, value).is_err() {
+        return -INFINITY;
+    }
+    let same_side = (game.get_current_turn() == root_player) == mover_is_root;
+    let result = if same_side {
+        score(game, alpha, beta)
+    } else {
+        -score(game, -beta, -alpha)
+    };
+    game.undo();
+    result
+}
+
+/// Sorts moves so that alpha-beta search cuts off early: the best move stored for the position
+/// first, then the moves completing the most SOS, the killer move and the moves that do not hand
+/// the next player an SOS.
+pub(crate) fn order_moves(game: &Game, moves: &mut [(u32, CellValue, u16)], hint: Option<(u32, CellValue)>, killer: Option<(u32, CellValue)>) {
+    moves.sort_by_key(|&(pos, value, gain)| {
+        let mv = Some((pos, value));
+        std::cmp::Reverse((hint == mv, gain, killer == mv, !game.get_threat(pos).is_dangerous(value)))
+    });
+}
+
+impl Strategy for Search {
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)> {
+        self.best_move(game).map(|result| (result.pos, result.value))
+    }
+}
+
+fn start_clock(time: Option<Duration>) -> Option<Instant> {
+    Some(now()? + time?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<Instant> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    fn limits(depth: u8, nodes: u64) -> SearchLimits {
+        SearchLimits { depth, nodes, time: None }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_move() {
+        let game = Game::from_notation("5x5 S4/1O3/5/3O1/4S 1 0,0").unwrap();
+        let first = Search::new(limits(3, u64::MAX), 4).best_move(&game);
+        assert!(first.is_some());
+        assert_eq!(Search::new(limits(3, u64::MAX), 4).best_move(&game), first);
+    }
+
+    #[test]
+    fn completes_an_open_sos() {
+        for depth in 1..=3 {
+            let game = Game::from_notation("4x4 SO2/4/4/4 1 0,0").unwrap();
+            let result = Search::new(limits(depth, u64::MAX), 0).best_move(&game).unwrap();
+            assert_eq!((result.pos, result.value), (2, CellValue::S));
+        }
+    }
+
+    #[test]
+    fn finds_the_forced_win_of_a_simple_game() {
+        // Every move but the S at d1 lets the opponent complete an SOS.
+        let game = Game::from_notation("1x4 S3 1 0,0 simple").unwrap();
+        let result = Search::new(limits(4, u64::MAX), 0).best_move(&game).unwrap();
+        assert_eq!((result.pos, result.value), (3, CellValue::S));
+        assert!(result.score >= WIN / 2);
+    }
+
+    #[test]
+    fn no_move_when_the_game_is_over() {
+        let game = Game::from_notation("1x3 SOS 1 1,0").unwrap();
+        assert_eq!(Search::new(limits(3, u64::MAX), 0).best_move(&game), None);
+    }
+
+    #[test]
+    fn stops_at_the_node_limit() {
+        let game = Game::new(6, 6, 2, Rules::classic()).unwrap();
+        let result = Search::new(limits(10, 2_000), 0).best_move(&game).unwrap();
+        assert!(result.nodes <= 2_000);
+        assert!(result.depth < 10);
+    }
+
+    #[test]
+    fn a_cut_short_first_iteration_still_gives_a_move() {
+        let game = Game::from_notation("4x4 SO2/4/4/4 1 0,0").unwrap();
+        let result = Search::new(limits(3, 1), 0).best_move(&game).unwrap();
+        assert_eq!(result.depth, 0);
+        assert_eq!((result.pos, result.value), (2, CellValue::S));
+        let game = Game::new(6, 6, 2, Rules::classic()).unwrap();
+        assert!(Search::new(limits(3, 1), 0).choose_move(&game).is_some());
+    }
+}
@@ -0,0 +1,249 @@
+//! Compact encoding of a game for links, using only characters that are safe in a URL.
+//!
+//! The code is a list of fields separated by `.`, starting with the format version and a kind:
+//!
+//! - `1.g.<rows>x<cols>.<players>.<rules>.<moves>` is a game played from an empty board.
+//!   `rules` is one digit, 0 for scoring, 1 for classic, 2 for simple and 3 for simple with an
+//!   extra turn. `moves` packs every move as `pos * 2 + letter` (0 for S, 1 for O) into LEB128
+//!   bytes, written in unpadded base64url.
+//! - `1.p.<position>.<moves>` is a game set up from a position, in the notation of `notation`
+//!   with the spaces replaced by `_`, and the moves played since packed as above. Codes without
+//!   the moves field are the position alone.
+//!
+//! Codes of other versions are rejected, new versions may change everything after the version.
+
+use crate::cell::CellValue;
+use crate::game::{Game, GameError};
+use crate::history::MoveRecord;
+use crate::notation::{parse_number, NotationError};
+use crate::rules::{GameMode, Rules};
+use std::fmt;
+
+pub const SHARE_VERSION: u32 = 1;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug)]
+pub enum ShareError {
+    UnsupportedVersion(String),
+    Invalid,
+    Notation(NotationError),
+    /// The move at this index, counting from 0, cannot be played.
+    InvalidMove(usize, GameError),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::UnsupportedVersion(version) => write!(f, "unsupported version `{}`", version),
+            ShareError::Invalid => f.write_str("the code is damaged"),
+            ShareError::Notation(error) => error.fmt(f),
+            ShareError::InvalidMove(index, error) => write!(f, "move {}: {}", index + 1, error),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// Encodes the game as it is on the board, moves taken back with `undo` are left out.
+pub fn encode(game: &Game) -> String {
+    let moves = encode_moves(game.get_history());
+    if let Some(setup) = game.get_setup() {
+        return format!("{}.p.{}.{}", SHARE_VERSION, setup.replace(' ', "_"), moves);
+    }
+    let rules = game.get_rules();
+    let rules = (rules.mode == GameMode::Simple) as u8 * 2 + rules.extra_turn_on_sos as u8;
+    format!(
+        "{}.g.{}x{}.{}.{}.{}",
+        SHARE_VERSION,
+        game.get_rows(),
+        game.get_cols(),
+        game.get_num_of_players(),
+        rules,
+        moves,
+    )
+}
+
+fn encode_moves(history: &[MoveRecord]) -> String {
+    let mut bytes = vec![];
+    for record in history {
+        let mut value = record.pos * 2 + (record.value == CellValue::O) as u32;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    to_base64(&bytes)
+}
+
+pub fn decode(code: &str) -> Result<Game, ShareError> {
+    let fields: Vec<&str> = code.trim().split('.').collect();
+    if fields[0] != SHARE_VERSION.to_string() {
+        return Err(ShareError::UnsupportedVersion(fields[0].to_string()));
+    }
+    match fields[1..] {
+        ["p", position] => Game::from_notation(&position.replace('_', " ")).map_err(ShareError::Notation),
+        ["p", position, moves] => {
+            let mut game = Game::from_notation(&position.replace('_', " ")).map_err(ShareError::Notation)?;
+            play_moves(&mut game, moves)?;
+            Ok(game)
+        }
+        ["g", size, players, rules, moves] => {
+            let (rows, cols) = size.split_once('x').ok_or(ShareError::Invalid)?;
+            let rows: u16 = parse_number(rows).ok_or(ShareError::Invalid)?;
+            let cols: u16 = parse_number(cols).ok_or(ShareError::Invalid)?;
+            let players: u8 = parse_number(players).ok_or(ShareError::Invalid)?;
+            if players == 0 {
+                return Err(ShareError::Invalid);
+            }
+            let rules = match rules {
+                "0" => Rules { mode: GameMode::Scoring, extra_turn_on_sos: false },
+                "1" => Rules { mode: GameMode::Scoring, extra_turn_on_sos: true },
+                "2" => Rules { mode: GameMode::Simple, extra_turn_on_sos: false },
+                "3" => Rules { mode: GameMode::Simple, extra_turn_on_sos: true },
+                _ => return Err(ShareError::Invalid),
+            };
+
+            let mut game = Game::new(rows, cols, players, rules).map_err(|_| ShareError::Invalid)?;
+            play_moves(&mut game, moves)?;
+            Ok(game)
+        }
+        _ => Err(ShareError::Invalid),
+    }
+}
+
+fn play_moves(game: &mut Game, moves: &str) -> Result<(), ShareError> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    for byte in from_base64(moves)? {
+        if shift > 28 {
+            return Err(ShareError::Invalid);
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        let pos = u32::try_from(value / 2).map_err(|_| ShareError::Invalid)?;
+        let letter = if value & 1 == 0 { CellValue::S } else { CellValue::O };
+        let index = game.get_history().len();
+        game.update(game.get_current_turn(), pos, letter)
+            .map_err(|error| ShareError::InvalidMove(index, error))?;
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 {
+        return Err(ShareError::Invalid);
+    }
+    Ok(())
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            text.push(BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    text
+}
+
+fn from_base64(text: &str) -> Result<Vec<u8>, ShareError> {
+    let mut bytes = vec![];
+    let mut bits: u32 = 0;
+    let mut count = 0;
+    for c in text.bytes() {
+        let digit = BASE64.iter().position(|&d| d == c).ok_or(ShareError::Invalid)?;
+        bits = bits << 6 | digit as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played(mut game: Game, moves: &[(u32, CellValue)]) -> Game {
+        for &(pos, value) in moves {
+            game.make_move(pos, value).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn games_from_an_empty_board_round_trip() {
+        let game = played(Game::new(3, 4, 3, Rules::classic()).unwrap(), &[(0, CellValue::S), (1, CellValue::O), (2, CellValue::S), (11, CellValue::O)]);
+        let code = encode(&game);
+        assert!(code.starts_with("1.g.3x4.3.1."), "{}", code);
+        assert_eq!(decode(&code).unwrap(), game);
+        // Positions past 63 take more than one byte.
+        let game = played(Game::new(100, 100, 2, Rules::default()).unwrap(), &[(9999, CellValue::O), (64, CellValue::S)]);
+        assert_eq!(decode(&encode(&game)).unwrap(), game);
+        let empty = Game::new(3, 3, 2, Rules::default()).unwrap();
+        assert_eq!(encode(&empty), "1.g.3x3.2.0.");
+        assert_eq!(decode("1.g.3x3.2.0.").unwrap(), empty);
+    }
+
+    #[test]
+    fn undone_moves_are_left_out() {
+        let mut game = played(Game::new(3, 3, 2, Rules::default()).unwrap(), &[(0, CellValue::S), (4, CellValue::O)]);
+        game.undo();
+        let decoded = decode(&encode(&game)).unwrap();
+        assert_eq!(decoded.get_history(), game.get_history());
+        assert!(!decoded.can_redo());
+    }
+
+    #[test]
+    fn games_from_a_position_round_trip() {
+        let game = played(Game::from_notation("3x3 S1S/1O1/3 1 0,0 classic").unwrap(), &[(1, CellValue::O), (6, CellValue::S)]);
+        let code = encode(&game);
+        assert!(code.starts_with("1.p.3x3_S1S/1O1/3_1_0,0_classic."), "{}", code);
+        let decoded = decode(&code).unwrap();
+        assert_eq!(decoded, game);
+        assert_eq!(decoded.get_setup(), Some("3x3 S1S/1O1/3 1 0,0 classic"));
+        // Codes without moves are the position alone.
+        assert_eq!(decode("1.p.3x3_S1S/1O1/3_1_0,0_classic").unwrap().to_notation(), "3x3 S1S/1O1/3 1 0,0 classic");
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        for code in [
+            "1",
+            "1.x.3x3",
+            "1.g.3x3.2.0",
+            "1.g.3x3.2.0..",
+            "1.g.3.2.0.",
+            "1.g.0x3.2.0.",
+            "1.g.3x+3.2.0.",
+            "1.g.3x3.0.0.",
+            "1.g.3x3.2.4.",
+            "1.g.3x3.2.0.A*",
+            // A move whose last byte still has the continuation bit set.
+            "1.g.3x3.2.0.gA",
+            // A varint longer than five bytes.
+            "1.g.3x3.2.0.gICAgIAB",
+            "1.p.3x3_S1S/1O1",
+            "1.p.3x3_S1S/1O1/3_1_0,0.A*",
+        ] {
+            assert!(matches!(decode(code), Err(ShareError::Invalid | ShareError::Notation(_))), "{}", code);
+        }
+        for code in ["", "2.g.3x3.2.0.", "v1.g.3x3.2.0."] {
+            assert!(matches!(decode(code), Err(ShareError::UnsupportedVersion(_))), "{}", code);
+        }
+        // Both moves are S at a1.
+        assert!(matches!(decode("1.g.3x3.2.0.AAA"), Err(ShareError::InvalidMove(1, GameError::PositionAlreadyOccupied))));
+        assert!(matches!(decode("1.g.3x3.2.0.Eg"), Err(ShareError::InvalidMove(0, GameError::InvalidPosition))));
+    }
+}
@@ -0,0 +1,240 @@
+//! Exact solving of endgames with few empty cells left.
+//!
+//! The solver plays every line to the end with alpha-beta pruning, so its result is what perfect
+//! play from everyone leads to rather than an estimate. Positions reached through different move
+//! orders are solved once, with their bounds kept in a `TranspositionTable`. The negamax is that of
+//! `Search` without a depth limit or an evaluation, with the same move ordering and table entries.
+
+use crate::cell::CellValue;
+use crate::game::Game;
+use crate::outcome::PlayerResult;
+use crate::search::{order_moves, score_move, Bound, Search, SearchEntry, INFINITY};
+use crate::transposition::TranspositionTable;
+use crate::zobrist;
+
+// About 8 MB, enough for the endgames the bots solve.
+const TABLE_ENTRIES: usize = 1 << 18;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SolverLimits {
+    /// Most empty cells of a position the solver takes on.
+    pub cells: u32,
+    /// Maximum number of positions visited before giving up. Endgames of the same size differ a
+    /// lot, so this is what bounds the time, also on wasm where there is no clock.
+    pub nodes: u64,
+}
+
+/// The perfect-play result of a position, see `Solver::solve`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Solution {
+    /// Final score of the player to move minus the best final score of the other players, when
+    /// everyone plays perfectly. The other players are assumed to play together against them.
+    pub margin: i32,
+    /// Every move reaching `margin`, in board order.
+    pub moves: Vec<(u32, CellValue)>,
+    /// Positions visited.
+    pub nodes: u64,
+}
+
+impl Solution {
+    /// Whether the player to move wins, draws or loses with perfect play.
+    pub fn result(&self) -> PlayerResult {
+        match self.margin {
+            margin if margin > 0 => PlayerResult::Win,
+            0 => PlayerResult::Draw,
+            _ => PlayerResult::Lose,
+        }
+    }
+}
+
+/// Solves positions within its limits.
+///
+/// The table is kept between solves, the positions after a reply were mostly visited while
+/// solving the move before. What was solved before running out of nodes stays in it too, so a
+/// later solve of the same endgame starts further along.
+pub struct Solver {
+    limits: SolverLimits,
+    // Entries are solved to the end of the game, their depth is always `u8::MAX`.
+    table: TranspositionTable<SearchEntry>,
+    root_player: u8,
+    root_key: u64,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Solver {
+    pub fn new(limits: SolverLimits) -> Self {
+        Self {
+            limits,
+            table: TranspositionTable::new(TABLE_ENTRIES),
+            root_player: 0,
+            root_key: 0,
+            nodes: 0,
+            aborted: false,
+        }
+    }
+
+    /// Whether `game` is still running and has few enough empty cells for `solve`.
+    pub fn can_solve(&self, game: &Game) -> bool {
+        !game.is_game_over() && game.board().count_empty() as u32 <= self.limits.cells
+    }
+
+    /// The perfect-play margin and moves of the player to move, or `None` when the game is over,
+    /// has too many empty cells or needs more nodes than the limit.
+    pub fn solve(&mut self, game: &Game) -> Option<Solution> {
+        if !self.can_solve(game) {
+            return None;
+        }
+        let mut game = game.clone();
+        self.root_player = game.get_current_turn();
+        // The margins are those of the root player, so every root player needs their own entries.
+        self.root_key = zobrist::root_key(self.root_player);
+        self.nodes = 0;
+        self.aborted = false;
+
+        let mut margin = -INFINITY;
+        let mut moves = vec![];
+        let mut root_moves = Search::generate_moves(&game);
+        order_moves(&game, &mut root_moves, None, None);
+        for (pos, value, _) in root_moves {
+            // Moves worse than the best so far only need to be shown to be worse, equal ones are
+            // solved exactly.
+            let score = score_move(&mut game, self.root_player, (pos, value), margin - 1, INFINITY, |game, alpha, beta| {
+                self.negamax(game, alpha, beta)
+            });
+            if self.aborted {
+                return None;
+            }
+            if score > margin {
+                margin = score;
+                moves = vec![(pos, value)];
+            } else if score == margin {
+                moves.push((pos, value));
+            }
+        }
+        moves.sort_by_key(|&(pos, value)| (pos, value == CellValue::O));
+        Some(Solution { margin, moves, nodes: self.nodes })
+    }
+
+    fn negamax(&mut self, game: &mut Game, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes > self.limits.nodes {
+            self.aborted = true;
+            return 0;
+        }
+        let sign = if game.get_current_turn() == self.root_player { 1 } else { -1 };
+        if game.is_game_over() {
+            return sign * game.margin(self.root_player);
+        }
+
+        let key = game.get_hash() ^ self.root_key;
+        let entry = self.table.get(key);
+        if let Some(score) = entry.and_then(|entry| entry.cutoff(alpha, beta)) {
+            return score;
+        }
+        let mut moves = Search::generate_moves(game);
+        order_moves(game, &mut moves, entry.and_then(|entry| entry.best), None);
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for (pos, value, _) in moves {
+            let score = score_move(game, self.root_player, (pos, value), alpha, beta, |game, alpha, beta| {
+                self.negamax(game, alpha, beta)
+            });
+            // Nothing is stored, the moves not searched could have changed the result.
+            if self.aborted {
+                return 0;
+            }
+            if score > best {
+                best = score;
+                best_move = Some((pos, value));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = Bound::of(best, original_alpha, beta);
+        self.table.insert(key, SearchEntry { depth: u8::MAX, score: best, bound, best: best_move });
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    fn solve(notation: &str) -> Solution {
+        let game = Game::from_notation(notation).expect("valid position");
+        Solver::new(SolverLimits { cells: 16, nodes: u64::MAX }).solve(&game).expect("solvable position")
+    }
+
+    // The margin found by trying every line without pruning or memoization.
+    fn brute_force(game: &mut Game, root_player: u8) -> i32 {
+        if game.is_game_over() {
+            let scores = game.get_scores();
+            let best_other = (0..scores.len()).filter(|&player| player != root_player as usize).map(|player| scores[player] as i32).max().unwrap_or(0);
+            return scores[root_player as usize] as i32 - best_other;
+        }
+        let root_to_move = game.get_current_turn() == root_player;
+        let moves: Vec<(u32, CellValue)> = game.legal_moves().collect();
+        let scores = moves.into_iter().map(|(pos, value)| {
+            game.make_move(pos, value).unwrap();
+            let score = brute_force(game, root_player);
+            game.undo();
+            score
+        });
+        if root_to_move { scores.max().unwrap() } else { scores.min().unwrap() }
+    }
+
+    #[test]
+    fn takes_the_last_sos() {
+        let solution = solve("1x3 S1S 1 0,0");
+        assert_eq!(solution.margin, 1);
+        assert_eq!(solution.moves, vec![(1, CellValue::O)]);
+        assert_eq!(solution.result(), PlayerResult::Win);
+    }
+
+    #[test]
+    fn avoids_handing_over_an_sos() {
+        // Any letter at b1 or c1 lets the opponent complete an SOS, the S at c1 does not.
+        let solution = solve("1x4 S3 1 0,0");
+        assert_eq!(solution.moves, vec![(3, CellValue::S)]);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for notation in [
+            "3x3 S1S/1O1/1S1 1 0,0",
+            "3x3 SOS/1O1/3 1 1,0",
+            "3x3 S2/1O1/S2 2 0,0 classic",
+            "2x4 S3/3O 1 0,0 simple",
+            "3x3 1O1/S2/1O1 1 0,0,0",
+        ] {
+            let mut game = Game::from_notation(notation).expect("valid position");
+            let root_player = game.get_current_turn();
+            let expected = brute_force(&mut game, root_player);
+            assert_eq!(solve(notation).margin, expected, "{}", notation);
+        }
+    }
+
+    #[test]
+    fn refuses_large_positions() {
+        let game = Game::new(5, 5, 2, Rules::default()).unwrap();
+        assert!(Solver::new(SolverLimits { cells: 24, nodes: u64::MAX }).solve(&game).is_none());
+        assert!(Solver::new(SolverLimits { cells: 25, nodes: u64::MAX }).can_solve(&game));
+    }
+
+    #[test]
+    fn gives_up_at_the_node_limit() {
+        let game = Game::from_notation("3x4 S3/1O2/4 1 0,0 classic").unwrap();
+        let mut solver = Solver::new(SolverLimits { cells: 16, nodes: 100 });
+        assert_eq!(solver.solve(&game), None);
+        assert!(solver.nodes <= 101);
+        let solution = Solver::new(SolverLimits { cells: 16, nodes: u64::MAX }).solve(&game).unwrap();
+        assert!(solution.nodes > 100);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::cell::CellValue;
+use crate::game::Game;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Picks moves for a seat. Implemented by the built-in bots, and by anything else that should
+/// play the game, such as scripted opponents or test doubles.
+///
+/// The built-in bots take a seed and draw every random choice from it, so the seeds together
+/// with the moves reproduce a game exactly.
+pub trait Strategy {
+    /// Returns the move for the player to move, or `None` when there is nothing to play.
+    fn choose_move(&mut self, game: &Game) -> Option<(u32, CellValue)>;
+}
+
+/// The random numbers for choosing a move in `game`. They depend on the seed and the number of
+/// moves played, not on earlier choices, so a bot picks the same move after an undo or after
+/// the game was reloaded.
+pub(crate) fn move_rng(seed: u64, game: &Game) -> StdRng {
+    let moves = game.get_history().len() as u64;
+    StdRng::seed_from_u64(seed ^ moves.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
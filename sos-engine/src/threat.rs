@@ -0,0 +1,41 @@
+use crate::cell::CellValue;
+
+/// What each letter would do if it was played at an empty cell right now, see
+/// `Game::get_threat`. Occupied cells have no threats.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Threat {
+    /// The number of SOS an S here would complete.
+    pub s: u8,
+    /// The number of SOS an O here would complete.
+    pub o: u8,
+    /// An S here would leave an SOS with one cell missing, for the next player to complete.
+    pub s_dangerous: bool,
+    /// An O here would leave an SOS with one cell missing.
+    pub o_dangerous: bool,
+}
+
+impl Threat {
+    /// The number of SOS `value` would complete here.
+    pub fn count(&self, value: CellValue) -> u8 {
+        match value {
+            CellValue::S => self.s,
+            CellValue::O => self.o,
+            CellValue::Empty => 0,
+        }
+    }
+
+    /// Whether a letter here completes an SOS.
+    pub fn is_hot(&self) -> bool {
+        self.s > 0 || self.o > 0
+    }
+
+    /// Whether `value` here hands the next player an SOS.
+    pub fn is_dangerous(&self, value: CellValue) -> bool {
+        match value {
+            CellValue::S => self.s_dangerous,
+            CellValue::O => self.o_dangerous,
+            CellValue::Empty => false,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::mem::size_of;
+
+/// A fixed-size table of what searches found out about positions, keyed by `Game::get_hash`.
+///
+/// Every hash has one slot, and a new entry replaces whatever was stored in its slot, so the
+/// table never grows. The entry type is up to the search, e.g. `SearchEntry` for `Search`. A
+/// table can be kept between moves, or handed from one search to the next, to reuse the work.
+#[derive(Clone, Debug)]
+pub struct TranspositionTable<T> {
+    slots: Vec<Option<(u64, T)>>,
+    len: usize,
+}
+
+impl<T: Copy> TranspositionTable<T> {
+    /// A table of at least one and at most `capacity` entries, rounded down to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = if capacity <= 1 { 1 } else { 1 << (usize::BITS - 1 - capacity.leading_zeros()) };
+        Self { slots: vec![None; capacity], len: 0 }
+    }
+
+    /// A table taking up at most `megabytes` of memory, with at least one entry.
+    pub fn with_megabytes(megabytes: usize) -> Self {
+        TranspositionTable::new(megabytes.saturating_mul(1 << 20) / size_of::<Option<(u64, T)>>())
+    }
+
+    pub fn get(&self, hash: u64) -> Option<T> {
+        match self.slots[self.slot(hash)] {
+            Some((stored, value)) if stored == hash => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, hash: u64, value: T) {
+        let slot = self.slot(hash);
+        if self.slots[slot].is_none() {
+            self.len += 1;
+        }
+        self.slots[slot] = Some((hash, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+        self.len = 0;
+    }
+
+    /// The number of entries stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of entries the table can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        hash as usize & (self.slots.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_replace_the_one_in_their_slot() {
+        let mut table = TranspositionTable::new(6);
+        assert_eq!(table.capacity(), 4);
+        table.insert(1, 'a');
+        table.insert(2, 'b');
+        assert_eq!(table.get(1), Some('a'));
+        table.insert(5, 'c');
+        assert_eq!(table.get(1), None);
+        assert_eq!(table.get(5), Some('c'));
+        assert_eq!(table.len(), 2);
+        table.clear();
+        assert!(table.is_empty() && table.get(2).is_none());
+    }
+
+    #[test]
+    fn size_is_bounded() {
+        let table = TranspositionTable::<u64>::with_megabytes(1);
+        assert!(table.capacity() * size_of::<Option<(u64, u64)>>() <= 1 << 20);
+        assert_eq!(TranspositionTable::<u64>::with_megabytes(0).capacity(), 1);
+    }
+}
@@ -0,0 +1,56 @@
+//! Zobrist keys for hashing positions, see `Game::get_hash`.
+//!
+//! The hash of a position is the XOR of a key for the board size, players and rules, a key for
+//! every letter on the board, a key for the player to move and a key for every player's score
+//! minus the score of the first player. Equal scores do not matter to the rest of the game, only
+//! the differences do. The keys are derived from their cell, player or score on the fly, so large
+//! boards need no key tables.
+
+use crate::cell::CellValue;
+use crate::rules::{GameMode, Rules};
+
+// Each kind of key gets its own range of inputs, so two kinds never share a key.
+const TURN: u64 = 1 << 56;
+const SCORE: u64 = 2 << 56;
+const ROOT: u64 = 3 << 56;
+const SETUP: u64 = 4 << 56;
+
+/// SplitMix64, which turns consecutive numbers into well spread keys.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// The key of `value` at `pos`, 0 for an empty cell.
+pub(crate) fn cell_key(pos: u32, value: CellValue) -> u64 {
+    match value {
+        CellValue::Empty => 0,
+        CellValue::S => mix((pos as u64) << 1),
+        CellValue::O => mix((pos as u64) << 1 | 1),
+    }
+}
+
+/// The key of everything that stays the same for a whole game. The same letters mean something
+/// else on a board of another width or under other rules, so their positions must not share a
+/// hash.
+pub(crate) fn setup_key(rows: u16, cols: u16, players: u8, rules: Rules) -> u64 {
+    let rules = (rules.mode == GameMode::Simple) as u64 | (rules.extra_turn_on_sos as u64) << 1;
+    mix(SETUP | (rows as u64) << 34 | (cols as u64) << 18 | (players as u64) << 2 | rules)
+}
+
+/// The key of the player to move together with the scores.
+pub(crate) fn state_key(turn: u8, scores: &[u32]) -> u64 {
+    let mut key = mix(TURN | turn as u64);
+    for (player, &score) in scores.iter().enumerate().skip(1) {
+        let difference = score.wrapping_sub(scores[0]) as u64;
+        key ^= mix(SCORE | (player as u64) << 32 | difference);
+    }
+    key
+}
+
+/// A key for the player a search is run for, whose evaluations differ between players.
+pub(crate) fn root_key(player: u8) -> u64 {
+    mix(ROOT | player as u64)
+}
@@ -0,0 +1,171 @@
+use proptest::prelude::*;
+use proptest::sample::Index;
+use sos_engine::{CellValue, Game, GameMode, Rules, Threat};
+use std::collections::HashSet;
+
+/// A game played from an empty board, picking every move from the legal ones.
+fn arbitrary_game() -> impl Strategy<Value = Game> {
+    let rules = (any::<bool>(), any::<bool>()).prop_map(|(simple, extra_turn_on_sos)| Rules {
+        mode: if simple { GameMode::Simple } else { GameMode::Scoring },
+        extra_turn_on_sos,
+    });
+    (1..9u16, 1..9u16, 2..=4u8, rules, prop::collection::vec(any::<Index>(), 0..80)).prop_map(
+        |(rows, cols, players, rules, picks)| {
+            let mut game = Game::new(rows, cols, players, rules).unwrap();
+            for pick in picks {
+                let moves: Vec<(u32, CellValue)> = game.legal_moves().collect();
+                if moves.is_empty() {
+                    break;
+                }
+                let (pos, value) = *pick.get(&moves);
+                game.make_move(pos, value).unwrap();
+            }
+            game
+        },
+    )
+}
+
+/// Every SOS on the board, found by walking the rows, columns and diagonals.
+fn sos_on_board(game: &Game) -> HashSet<(u32, u32, u32)> {
+    let board = game.board();
+    let (rows, cols) = (board.get_rows() as i32, board.get_cols() as i32);
+    let letter = |row: i32, col: i32| {
+        if row < 0 || col < 0 || row >= rows || col >= cols {
+            return None;
+        }
+        board.get_at(row as u16, col as u16)
+    };
+    let mut found = HashSet::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                let cells = [letter(row, col), letter(row + dr, col + dc), letter(row + 2 * dr, col + 2 * dc)];
+                if cells == [Some(CellValue::S), Some(CellValue::O), Some(CellValue::S)] {
+                    let pos = |step: i32| ((row + step * dr) * cols + col + step * dc) as u32;
+                    found.insert(normalize((pos(0), pos(1), pos(2))));
+                }
+            }
+        }
+    }
+    found
+}
+
+/// The threats of the empty cell at `pos`, found by trying both letters in every line of three
+/// through it.
+fn threat_on_board(game: &Game, pos: u32) -> Threat {
+    let board = game.board();
+    let (rows, cols) = (board.get_rows() as i32, board.get_cols() as i32);
+    let (row, col) = coordinates(game, pos);
+    let mut threat = Threat::default();
+    for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+        for index in 0..3 {
+            let line: Vec<(i32, i32)> = (0..3).map(|step| (row + (step - index) * dr, col + (step - index) * dc)).collect();
+            if line.iter().any(|&(r, c)| r < 0 || c < 0 || r >= rows || c >= cols) {
+                continue;
+            }
+            let letters = [CellValue::S, CellValue::O, CellValue::S];
+            let others: Vec<CellValue> = (0..3)
+                .filter(|&step| step != index)
+                .map(|step| board.get_at(line[step as usize].0 as u16, line[step as usize].1 as u16).unwrap())
+                .collect();
+            let expected: Vec<CellValue> = (0..3).filter(|&step| step != index).map(|step| letters[step as usize]).collect();
+            let right = (0..2).filter(|&i| others[i] == expected[i]).count();
+            let empty = others.iter().filter(|&&cell| cell == CellValue::Empty).count();
+            match (letters[index as usize], right, empty) {
+                (CellValue::S, 2, _) => threat.s += 1,
+                (CellValue::O, 2, _) => threat.o += 1,
+                (CellValue::S, 1, 1) => threat.s_dangerous = true,
+                (CellValue::O, 1, 1) => threat.o_dangerous = true,
+                _ => {}
+            }
+        }
+    }
+    threat
+}
+
+fn normalize((a, b, c): (u32, u32, u32)) -> (u32, u32, u32) {
+    if a > c { (c, b, a) } else { (a, b, c) }
+}
+
+fn coordinates(game: &Game, pos: u32) -> (i32, i32) {
+    let (row, col) = game.board().coordinates(pos);
+    (row as i32, col as i32)
+}
+
+proptest! {
+    #[test]
+    fn total_score_is_the_number_of_sos_on_the_board(game in arbitrary_game()) {
+        let total: u32 = game.get_scores().iter().sum();
+        prop_assert_eq!(total as usize, sos_on_board(&game).len());
+    }
+
+    #[test]
+    fn no_sos_is_counted_twice(game in arbitrary_game()) {
+        let mut seen = HashSet::new();
+        for &sos in game.get_sos() {
+            prop_assert!(seen.insert(normalize(sos)), "{:?} counted twice in {}", sos, game.to_notation());
+        }
+        prop_assert_eq!(seen, sos_on_board(&game));
+    }
+
+    #[test]
+    fn no_sos_wraps_around_the_board(game in arbitrary_game()) {
+        for &(a, b, c) in game.get_sos() {
+            let (a, b, c) = (coordinates(&game, a), coordinates(&game, b), coordinates(&game, c));
+            let step = (b.0 - a.0, b.1 - a.1);
+            prop_assert_eq!(step, (c.0 - b.0, c.1 - b.1), "{:?} {:?} {:?} is not a line", a, b, c);
+            prop_assert!(step != (0, 0) && step.0.abs() <= 1 && step.1.abs() <= 1, "{:?} {:?} {:?} is not a line", a, b, c);
+        }
+    }
+
+    #[test]
+    fn bitboard_counts_match_the_listed_sos(game in arbitrary_game()) {
+        for pos in game.board().empty_cells() {
+            for value in [CellValue::S, CellValue::O] {
+                prop_assert_eq!(game.count_sos_for_move(pos, value), game.get_sos_for_move(pos, value).len());
+            }
+        }
+    }
+
+    #[test]
+    fn threats_are_kept_up_to_date(game in arbitrary_game()) {
+        for pos in game.board().empty_cells() {
+            prop_assert_eq!(game.get_threat(pos), threat_on_board(&game, pos), "at {} in {}", pos, game.to_notation());
+        }
+        let dangerous: Vec<u32> = game.board().empty_cells()
+            .filter(|&pos| {
+                let threat = threat_on_board(&game, pos);
+                threat.s_dangerous || threat.o_dangerous
+            })
+            .collect();
+        prop_assert_eq!(game.dangerous_cells().map(|(pos, _)| pos).collect::<Vec<u32>>(), dangerous);
+        let mut undone = game.clone();
+        undone.undo();
+        for pos in undone.board().empty_cells() {
+            prop_assert_eq!(undone.get_threat(pos), threat_on_board(&undone, pos));
+        }
+    }
+
+    #[test]
+    fn the_hash_only_depends_on_the_position(game in arbitrary_game()) {
+        let set_up = Game::from_notation(&game.to_notation()).unwrap();
+        prop_assert_eq!(game.get_hash(), set_up.get_hash());
+        let mut undone = game.clone();
+        if undone.undo().is_some() {
+            let set_up = Game::from_notation(&undone.to_notation()).unwrap();
+            prop_assert_eq!(undone.get_hash(), set_up.get_hash());
+            prop_assert_ne!(undone.get_hash(), game.get_hash());
+        }
+    }
+
+    #[test]
+    fn undo_takes_back_every_move(game in arbitrary_game()) {
+        let mut undone = game.clone();
+        while undone.undo().is_some() {}
+        prop_assert!(undone.get_sos().is_empty());
+        prop_assert!(undone.get_scores().iter().all(|&score| score == 0));
+        prop_assert_eq!(undone.board().count_empty(), undone.board().get_num_of_cells() as usize);
+        while undone.redo().is_some() {}
+        prop_assert_eq!(undone, game);
+    }
+}
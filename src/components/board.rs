@@ -1,36 +1,73 @@
 use crate::components::cell::Cell;
-use crate::components::constants::{COMPUTER_TURN, LINE_BOTTOM_CENTER, LINE_BOTTOM_LEFT, LINE_BOTTOM_RIGHT, LINE_CENTER_LEFT, LINE_CENTER_RIGHT, LINE_TOP_CENTER, LINE_TOP_LEFT, LINE_TOP_RIGHT, PLAYER_TURN};
+use crate::components::constants::{LINE_BOTTOM_CENTER, LINE_BOTTOM_LEFT, LINE_BOTTOM_RIGHT, LINE_CENTER_LEFT, LINE_CENTER_RIGHT, LINE_TOP_CENTER, LINE_TOP_LEFT, LINE_TOP_RIGHT, FORECAST, PLAYER_COLORS};
+use crate::components::settings::{GameSettings, PlayerKind};
+use crate::components::link;
+use crate::components::record_file;
+use crate::components::storage;
 use crate::components::state::{BoardEvents, BoardState};
-use crate::engine::cell::CellValue;
-use crate::engine::game::Game;
+use sos_engine::cell::CellValue;
+use sos_engine::bot::Bot;
+use sos_engine::controller::{Controller, Seat};
+use sos_engine::game::Game;
+use sos_engine::record::GameRecord;
+use sos_engine::outcome::{Outcome, PlayerResult, WinReason};
+use sos_engine::solver::Solver;
+use gloo_timers::callback::Interval;
 use gloo_timers::future::TimeoutFuture;
 use std::collections::HashMap;
 use std::rc::Rc;
-use yew::{html, Component, Context, ContextProvider, Html, Properties};
+use web_sys::HtmlInputElement;
+use yew::{html, Component, Context, ContextProvider, Html, InputEvent, Properties, TargetCast};
+
+// Autoplay delays between replayed moves, in milliseconds.
+const REPLAY_SPEEDS: [(u32, &str); 4] = [(2000, "0.5x"), (1000, "1x"), (500, "2x"), (250, "4x")];
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct BoardProps {
-    pub col: u16,
-    pub row: u16,
+    pub settings: GameSettings,
+    /// A saved game to continue instead of starting from an empty board.
+    #[prop_or_default]
+    pub game: Option<Game>,
 }
 
 pub enum BoardMsg {
-    Selecting((u16, CellValue)),
+    Selecting((u32, CellValue)),
     LockCells,
     UnlockCells,
     BotMove,
-    ProcessUpdate(u16, CellValue),
+    ProcessUpdate(u32, CellValue),
+    NextTurn,
     CheckGameOver,
+    Undo,
+    Redo,
+    Restore,
+    Export,
+    Share,
+    StartReplay,
+    StopReplay,
+    /// Shows the board after this many moves.
+    ReplayJump(usize),
+    ReplayStep(isize),
+    ToggleAutoplay,
+    SetReplaySpeed(u32),
 }
 pub struct Board {
     pub col: u16,
     pub row: u16,
     state:Rc<BoardState>,
-    game_engine: Game,
+    controller: Controller,
     turn: u8,
-    player_score: u16,
-    bot_score: u16,
-
+    scores: Vec<u32>,
+    busy: bool,
+    settings: GameSettings,
+    // Number of moves shown while replaying, `None` during normal play.
+    replay: Option<usize>,
+    autoplay: Option<Interval>,
+    replay_speed: u32,
+    share_link: Option<String>,
+    solver: Option<Solver>,
+    // The result of the player to move with best play, once the endgame is small enough to solve.
+    forecast: Option<PlayerResult>,
 }
 
 impl Component for Board {
@@ -41,21 +78,41 @@ impl Component for Board {
         let state = Rc::new(BoardState{
             events: BoardEvents::Idle,
         });
+        let settings = ctx.props().settings.clone();
+        let game = ctx.props().game.clone()
+            .unwrap_or_else(|| {
+                Game::new(settings.row, settings.col, settings.players.len() as u8, settings.rules)
+                    .expect("the settings keep the board size in range")
+            });
         Self {
-            turn: PLAYER_TURN,
+            turn: 0,
             state,
-            col: ctx.props().col,
-            row: ctx.props().row,
-            game_engine: Game::new(ctx.props().row, ctx.props().col,2),
-            player_score: 0,
-            bot_score: 0,
+            col: settings.col,
+            row: settings.row,
+            controller: Board::new_controller(&settings, game),
+            scores: vec![0; settings.players.len()],
+            busy: false,
+            settings,
+            replay: None,
+            autoplay: None,
+            replay_speed: 1000,
+            share_link: None,
+            solver: None,
+            forecast: None,
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        // Draw a restored game once the cells exist, this also lets a bot in the first seat move.
+        if first_render {
+            ctx.link().send_message(BoardMsg::Restore);
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             BoardMsg::Selecting((id, val)) => {
-                if self.turn == COMPUTER_TURN {
+                if self.replay.is_some() || self.controller.is_computer_turn() {
                     return false;
                 }
                 if val == CellValue::Empty {
@@ -67,25 +124,40 @@ impl Component for Board {
                     TimeoutFuture::new(100).await;
                     link.send_message(BoardMsg::ProcessUpdate(id, val));
                     TimeoutFuture::new(100).await;
-                    link.send_message(BoardMsg::BotMove);
-                    TimeoutFuture::new(100).await;
-                    link.send_message(BoardMsg::CheckGameOver);
-                    TimeoutFuture::new(100).await;
-                    link.send_message(BoardMsg::UnlockCells);
+                    link.send_message(BoardMsg::NextTurn);
                 });
 
                 false
             },
+            BoardMsg::NextTurn => {
+                // The same side may move several times in a row when the rules grant extra turns,
+                // so keep handing the move to the bots until it is a player's turn again.
+                let link = ctx.link().clone();
+                if self.controller.game().is_game_over() {
+                    link.send_message(BoardMsg::CheckGameOver);
+                } else if self.controller.is_computer_turn() {
+                    wasm_bindgen_futures::spawn_local( async move {
+                        link.send_message(BoardMsg::BotMove);
+                        TimeoutFuture::new(100).await;
+                        link.send_message(BoardMsg::NextTurn);
+                    });
+                } else {
+                    link.send_message(BoardMsg::UnlockCells);
+                }
+                false
+            },
             BoardMsg::LockCells => {
+                self.busy = true;
+                self.forecast = None;
                 Rc::make_mut(&mut self.state).events = BoardEvents::Lock;
                 true
             }
             BoardMsg::ProcessUpdate(id, val) => {
-                let res = self.game_engine.update(self.turn, id, val);
+                let res = self.controller.play(id, val);
                 match res {
                     Ok(result) => {
-                        self.player_score = result.scores[0];
-                        self.bot_score = result.scores[1];
+                        storage::save(&self.settings, self.controller.game());
+                        self.scores = result.scores;
                         self.turn = result.next_turn;
                         let mut map = HashMap::new();
                         map.insert(id, (0,Some(val)));
@@ -99,26 +171,32 @@ impl Component for Board {
                 }
             }
             BoardMsg::UnlockCells => {
-                if self.game_engine.is_game_over() {
+                if self.controller.game().is_game_over() {
                     return false;
                 }
+                self.busy = false;
+                // Nothing is shown when the endgame is still too big or too hard to solve in time.
+                self.forecast = None;
+                if self.controller.game().board().count_empty() as u32 <= FORECAST.cells {
+                    let solver = self.solver.get_or_insert_with(|| Solver::new(FORECAST));
+                    self.forecast = solver.solve(self.controller.game()).map(|solution| solution.result());
+                }
                 Rc::make_mut(&mut self.state).events = BoardEvents::Unlock;
                 true
             },
             BoardMsg::BotMove => {
-                if self.turn != COMPUTER_TURN || self.game_engine.is_game_over() {
+                if !self.controller.is_computer_turn() {
                     return false;
                 }
-                let response = self.game_engine.bot_move();
+                let response = self.controller.play_computer_turn();
                 match response {
-                    Ok((pos, val, sos)) => {
-                        self.turn = self.game_engine.get_current_turn();
+                    Ok((pos, val, result)) => {
+                        storage::save(&self.settings, self.controller.game());
+                        self.turn = self.controller.game().get_current_turn();
                         let mut map = HashMap::new();
                         map.insert(pos, (0,Some(val)));
-                        self.grouping_sos(&sos, &mut map);
-                        let scores = self.game_engine.get_scores();
-                        self.player_score = scores[0];
-                        self.bot_score = scores[1];
+                        self.grouping_sos(&result.new_sos, &mut map);
+                        self.scores = result.scores;
                         Rc::make_mut(&mut self.state).events = BoardEvents::Update(map);
                     }
                     Err(_e) => {}
@@ -126,54 +204,364 @@ impl Component for Board {
                 true
             },
             BoardMsg::CheckGameOver => {
-                if self.game_engine.is_game_over() {
+                if self.controller.game().is_game_over() {
+                    storage::clear();
+                    self.busy = false;
                     Rc::make_mut(&mut self.state).events = BoardEvents::Lock;
                     return true
                 }
                 false
 
             }
+            BoardMsg::Undo => {
+                if self.busy || !self.controller.game().can_undo() {
+                    return false;
+                }
+                // Take back the bots' replies together with the player's move before them.
+                while let Some(record) = self.controller.undo() {
+                    if !self.controller.is_computer(record.player) {
+                        break;
+                    }
+                }
+                self.reset_cells(ctx);
+                true
+            }
+            BoardMsg::Redo => {
+                if self.busy || !self.controller.game().can_redo() {
+                    return false;
+                }
+                self.controller.redo();
+                while self.controller.game().can_redo() && self.controller.is_computer_turn() {
+                    self.controller.redo();
+                }
+                self.reset_cells(ctx);
+                true
+            }
+            BoardMsg::Restore => {
+                self.reset_cells(ctx);
+                true
+            }
+            BoardMsg::Export => {
+                let names = self.settings.players.iter().map(|player| player.name.clone()).collect();
+                let mut record = GameRecord::from_game(self.controller.game(), names, Some(record_file::today()));
+                if self.settings.has_computer() {
+                    record.tags.push(("Seed".to_string(), self.settings.seed.to_string()));
+                }
+                record_file::download("game.sos", &record.write());
+                false
+            }
+            BoardMsg::Share => {
+                self.share_link = Some(link::share(self.controller.game()));
+                true
+            }
+            BoardMsg::StartReplay => {
+                if self.busy || self.replay.is_some() {
+                    return false;
+                }
+                self.replay = Some(self.controller.game().get_history().len());
+                Rc::make_mut(&mut self.state).events = BoardEvents::Lock;
+                true
+            }
+            BoardMsg::StopReplay => {
+                if self.replay.take().is_none() {
+                    return false;
+                }
+                self.autoplay = None;
+                self.reset_cells(ctx);
+                true
+            }
+            BoardMsg::ReplayJump(moves) => {
+                let Some(current) = self.replay else {
+                    return false;
+                };
+                let moves = moves.min(self.controller.game().get_history().len());
+                if moves == self.controller.game().get_history().len() {
+                    self.autoplay = None;
+                }
+                if moves == current {
+                    return true;
+                }
+                self.replay = Some(moves);
+                let shown = self.replayed(moves);
+                let mut map = HashMap::new();
+                for (i, val) in shown.board().cells().iter().enumerate() {
+                    if *val != CellValue::Empty {
+                        map.insert(i as u32, (0, Some(*val)));
+                    }
+                }
+                let sos = shown.get_sos().to_vec();
+                self.grouping_sos(&sos, &mut map);
+                Rc::make_mut(&mut self.state).events = BoardEvents::Reset(map);
+                true
+            }
+            BoardMsg::ReplayStep(step) => {
+                let Some(current) = self.replay else {
+                    return false;
+                };
+                let moves = current.saturating_add_signed(step);
+                ctx.link().send_message(BoardMsg::ReplayJump(moves));
+                false
+            }
+            BoardMsg::ToggleAutoplay => {
+                if self.autoplay.take().is_some() {
+                    return true;
+                }
+                // Playing from the last move starts over from the empty board.
+                if self.replay == Some(self.controller.game().get_history().len()) {
+                    ctx.link().send_message(BoardMsg::ReplayJump(0));
+                }
+                let link = ctx.link().clone();
+                self.autoplay = Some(Interval::new(self.replay_speed, move || link.send_message(BoardMsg::ReplayStep(1))));
+                true
+            }
+            BoardMsg::SetReplaySpeed(speed) => {
+                self.replay_speed = speed;
+                if self.autoplay.is_some() {
+                    let link = ctx.link().clone();
+                    self.autoplay = Some(Interval::new(speed, move || link.send_message(BoardMsg::ReplayStep(1))));
+                }
+                true
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let state = self.state.clone();
         let onselect = ctx.link().callback(BoardMsg::Selecting);
-        let cells = (0..self.col*self.row).map(|i| html! { <Cell id={i} onselect={onselect.clone()}/>  });
+        let cells = (0..self.col as u32 * self.row as u32).map(|i| html! { <Cell id={i} onselect={onselect.clone()}/>  });
         let style = format!("grid-template-columns: repeat({}, 1fr);grid-template-rows: repeat({}, 1fr);", self.col, self.row);
-        let message = if self.game_engine.is_game_over() {
-            match self.player_score.cmp(&self.bot_score) {
-                std::cmp::Ordering::Greater => html!(<p class="win-bar">{"You win!"}</p>),
-                std::cmp::Ordering::Less => html!(<p class="lose-bar">{"You lose!"}</p>),
-                std::cmp::Ordering::Equal => html!(<p class="draw-bar">{"Draw"}</p>),
-            }
-        } else if self.turn == 0 {
-            html!(<p class="turn">{"Your turn"}</p>)
-        } else {
-            html!(<p class="turn">{"Computer turn"}</p>)
+        let outcome = self.controller.game().outcome();
+        let message = match &outcome {
+            _ if self.replay.is_some() => self.replay_message(),
+            Some(outcome) => self.outcome_message(outcome),
+            None if Some(self.turn) == self.only_human() => html!(<p class="turn">{"Your turn"}</p>),
+            None => html! {
+                <p class="turn" style={format!("color:{};", PLAYER_COLORS[self.turn as usize])}>
+                    {format!("{}'s turn", self.settings.players[self.turn as usize].name)}
+                </p>
+            },
+        };
+
+        let forecast = match self.forecast {
+            _ if self.busy || self.replay.is_some() || outcome.is_some() => None,
+            Some(PlayerResult::Win) => Some("Forced win in this position"),
+            Some(PlayerResult::Draw) => Some("Forced draw in this position"),
+            Some(PlayerResult::Lose) => Some("Forced loss in this position"),
+            None => None,
+        };
+
+        let can_undo = !self.busy && self.controller.game().can_undo();
+        let can_redo = !self.busy && self.controller.game().can_redo();
+        let can_replay = !self.busy && self.controller.game().can_undo();
+        let shown_scores = match self.replay {
+            Some(moves) => self.replayed(moves).get_scores(),
+            None => self.scores.clone(),
         };
+        let scores = shown_scores.iter().enumerate().map(|(player, score)| {
+            let class = if outcome.is_none() && self.replay.is_none() && player == self.turn as usize { "score current" } else { "score" };
+            html! {
+                <span class={class} style={format!("color:{};", PLAYER_COLORS[player])}>
+                    {format!("{}: {}", self.settings.players[player].name, score)}
+                </span>
+            }
+        });
+        let results = outcome.as_ref().map(|outcome| self.ranking(outcome)).unwrap_or_default().into_iter().map(|(rank, player)| {
+            html! {
+                <div class="result" style={format!("color:{};", PLAYER_COLORS[player as usize])}>
+                    <span>{format!("{}. {}", rank, self.settings.players[player as usize].name)}</span>
+                    <span>{self.scores[player as usize]}</span>
+                </div>
+            }
+        });
 
         html! {
             <>
             <div class="scoreboard">
-                <span class="your-score">{ "Your Score: " } {self.player_score}</span>
-                <span class="computer-score">{ "Computer Score: "} {self.bot_score}</span>
+                {for scores}
             </div>
             <div class="turn center">
             { message }
+            if let Some(forecast) = forecast {
+                <p class="forecast">{forecast}</p>
+            }
             </div>
+            if outcome.is_some() && self.replay.is_none() {
+                <div class="results center">
+                    {for results}
+                </div>
+            }
             <ContextProvider<Rc<BoardState>> context={state}>
                 <div class="grid center" style={style}>
                     {for cells}
                 </div>
              </ContextProvider<Rc<BoardState>>>
+            if let Some(moves) = self.replay {
+                { self.replay_controls(ctx, moves) }
+            } else {
+                <div class="controls center">
+                    <button class="control" disabled={!can_undo} onclick={ctx.link().callback(|_| BoardMsg::Undo)}>{"Undo"}</button>
+                    <button class="control" disabled={!can_redo} onclick={ctx.link().callback(|_| BoardMsg::Redo)}>{"Redo"}</button>
+                    <button class="control" disabled={!can_replay} onclick={ctx.link().callback(|_| BoardMsg::StartReplay)}>{"Replay"}</button>
+                    <button class="control" onclick={ctx.link().callback(|_| BoardMsg::Export)}>{"Export"}</button>
+                    <button class="control" onclick={ctx.link().callback(|_| BoardMsg::Share)}>{"Share"}</button>
+                </div>
+                if let Some(share_link) = &self.share_link {
+                    <input class="share-link center" type="text" readonly=true value={share_link.clone()}/>
+                }
+            }
             </>
         }
     }
 }
 
 impl Board {
-    fn grouping_sos(&mut self, result: &Vec<(u16, u16, u16)>, map: &mut HashMap<u16, (u8, Option<CellValue>)>) {
+    fn new_controller(settings: &GameSettings, game: Game) -> Controller {
+        let seats = settings.players.iter().enumerate().map(|(seat, player)| match player.kind {
+            PlayerKind::Computer => Seat::Computer(Box::new(Bot::new(settings.difficulty, settings.seed.wrapping_add(seat as u64)))),
+            PlayerKind::Human => Seat::Human,
+        }).collect();
+        Controller::new(game, seats).expect("one seat per player")
+    }
+
+    fn replay_message(&self) -> Html {
+        let moves = self.replay.unwrap_or(0);
+        let history = self.controller.game().get_history();
+        match moves.checked_sub(1).map(|index| &history[index]) {
+            Some(record) => html! {
+                <p class="turn" style={format!("color:{};", PLAYER_COLORS[record.player as usize])}>
+                    {format!("Move {} of {}: {} played {}", moves, history.len(),
+                        self.settings.players[record.player as usize].name,
+                        self.controller.game().format_move(record.pos, record.value))}
+                </p>
+            },
+            None => html!(<p class="turn">{format!("Start of the game, {} moves", history.len())}</p>),
+        }
+    }
+
+    fn replay_controls(&self, ctx: &Context<Self>, moves: usize) -> Html {
+        let link = ctx.link();
+        let total = self.controller.game().get_history().len();
+        let on_slide = link.callback(|e: InputEvent| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            BoardMsg::ReplayJump(value.parse().unwrap_or(0))
+        });
+        let speeds = REPLAY_SPEEDS.map(|(speed, label)| {
+            let class = if self.replay_speed == speed { "control selected" } else { "control" };
+            html! { <button class={class} onclick={link.callback(move |_| BoardMsg::SetReplaySpeed(speed))}>{label}</button> }
+        });
+        html! {
+            <>
+            <input class="replay-slider center" type="range" min="0" max={total.to_string()} value={moves.to_string()} oninput={on_slide}/>
+            <div class="controls center">
+                <button class="control" disabled={moves == 0} onclick={link.callback(|_| BoardMsg::ReplayJump(0))}>{"|<"}</button>
+                <button class="control" disabled={moves == 0} onclick={link.callback(|_| BoardMsg::ReplayStep(-1))}>{"<"}</button>
+                <button class="control" onclick={link.callback(|_| BoardMsg::ToggleAutoplay)}>
+                    { if self.autoplay.is_some() { "Pause" } else { "Play" } }
+                </button>
+                <button class="control" disabled={moves == total} onclick={link.callback(|_| BoardMsg::ReplayStep(1))}>{">"}</button>
+                <button class="control" disabled={moves == total} onclick={link.callback(move |_| BoardMsg::ReplayJump(total))}>{">|"}</button>
+            </div>
+            <div class="controls center">
+                {for speeds}
+                <button class="control" onclick={link.callback(|_| BoardMsg::StopReplay)}>{"Back to game"}</button>
+            </div>
+            </>
+        }
+    }
+
+    // The game after its first `moves` moves, starting from the setup of a set up game.
+    fn replayed(&self, moves: usize) -> Game {
+        let mut game = self.controller.game().clone();
+        while game.get_history().len() > moves {
+            game.undo();
+        }
+        game
+    }
+
+    // When a single person plays against bots, messages address them directly.
+    fn only_human(&self) -> Option<u8> {
+        let mut humans = (0..self.settings.players.len() as u8).filter(|&player| !self.controller.is_computer(player));
+        match (humans.next(), humans.next()) {
+            (Some(player), None) => Some(player),
+            _ => None,
+        }
+    }
+
+    fn outcome_message(&self, outcome: &Outcome) -> Html {
+        let first_sos = match outcome {
+            Outcome::Win { player, reason: WinReason::FirstSos } => format!(" {} made the first SOS.", self.settings.players[*player as usize].name),
+            _ => String::new(),
+        };
+        if let Some(human) = self.only_human() {
+            return match outcome.result_for(human) {
+                PlayerResult::Win => html!(<p class="win-bar">{format!("You win!{}", first_sos)}</p>),
+                PlayerResult::Lose => html!(<p class="lose-bar">{format!("You lose!{}", first_sos)}</p>),
+                PlayerResult::Draw => html!(<p class="draw-bar">{"Draw"}</p>),
+            };
+        }
+        match outcome {
+            Outcome::Win { player, .. } => html!(<p class="win-bar">{format!("{} wins!{}", self.settings.players[*player as usize].name, first_sos)}</p>),
+            Outcome::Draw { players, .. } => {
+                let names: Vec<&str> = players.iter().map(|&player| self.settings.players[player as usize].name.as_str()).collect();
+                html!(<p class="draw-bar">{format!("Draw between {}", names.join(" and "))}</p>)
+            }
+        }
+    }
+
+    // Orders all players by result and then by score, players that finish level share a rank.
+    fn ranking(&self, outcome: &Outcome) -> Vec<(usize, u8)> {
+        let key = |player: u8| {
+            let result = match outcome.result_for(player) {
+                PlayerResult::Win => 0,
+                PlayerResult::Draw => 1,
+                PlayerResult::Lose => 2,
+            };
+            (result, std::cmp::Reverse(self.scores[player as usize]))
+        };
+        let mut players: Vec<u8> = (0..self.settings.players.len() as u8).collect();
+        players.sort_by_key(|&player| key(player));
+
+        let mut ranking: Vec<(usize, u8)> = Vec::with_capacity(players.len());
+        for (i, &player) in players.iter().enumerate() {
+            let rank = match ranking.last() {
+                Some(&(rank, previous)) if key(previous) == key(player) => rank,
+                _ => i + 1,
+            };
+            ranking.push((rank, player));
+        }
+        ranking
+    }
+
+    // Redraws every cell from the engine after the history was moved, then resumes play.
+    fn reset_cells(&mut self, ctx: &Context<Self>) {
+        // A board without moves, new or undone to the start, is nothing to resume.
+        if self.controller.game().get_history().is_empty() {
+            storage::clear();
+        } else {
+            storage::save(&self.settings, self.controller.game());
+        }
+        self.scores = self.controller.game().get_scores();
+        self.turn = self.controller.game().get_current_turn();
+
+        let mut map = HashMap::new();
+        for (i, val) in self.controller.game().board().cells().iter().enumerate() {
+            if *val != CellValue::Empty {
+                map.insert(i as u32, (0, Some(*val)));
+            }
+        }
+        let sos = self.controller.game().get_sos().to_vec();
+        self.grouping_sos(&sos, &mut map);
+
+        self.busy = true;
+        Rc::make_mut(&mut self.state).events = BoardEvents::Reset(map);
+        let link = ctx.link().clone();
+        wasm_bindgen_futures::spawn_local( async move {
+            TimeoutFuture::new(100).await;
+            link.send_message(BoardMsg::NextTurn);
+        });
+    }
+
+    fn grouping_sos(&mut self, result: &Vec<(u32, u32, u32)>, map: &mut HashMap<u32, (u8, Option<CellValue>)>) {
         for (x,y,z) in result {
             if !map.contains_key(&x) {
                 map.insert(*x, (0, None));
@@ -185,11 +573,11 @@ impl Board {
                 map.insert(*z, (0, None));
             }
 
-            let i = *x as i16;
-            let k = *z as i16;
+            let i = *x as i64;
+            let k = *z as i64;
 
             match (x, y, z) {
-                (x,y,z) if (i + 2) + (self.col as i16 )* 2 == k => { //diagonal left to right
+                (x,y,z) if (i + 2) + (self.col as i64 )* 2 == k => { //diagonal left to right
                     let (mut a, val) = map[&x];
                     a |= LINE_BOTTOM_RIGHT;
                     map.insert(*x, (a, val));
@@ -202,7 +590,7 @@ impl Board {
                     c |= LINE_TOP_LEFT;
                     map.insert(*z, (c, val));
                 },
-                (x,y,z) if i + (self.col as i16 ) * 2 == k => {  // vertical
+                (x,y,z) if i + (self.col as i64 ) * 2 == k => {  // vertical
                     let (mut a, val) = map[&x];
                     a |= LINE_BOTTOM_CENTER;
                     map.insert(*x, (a, val));
@@ -215,7 +603,7 @@ impl Board {
                     c |= LINE_TOP_CENTER;
                     map.insert(*z, (c, val));
                 },
-                (x,y,z) if (i-2) + (self.col as i16 )* 2 == k => { //diagonal right to left
+                (x,y,z) if (i-2) + (self.col as i64 )* 2 == k => { //diagonal right to left
                     let (mut a, val) = map[&x];
                     a |= LINE_BOTTOM_LEFT;
                     map.insert(*x, (a, val));
@@ -1,6 +1,6 @@
 use crate::components::constants::{LINE_BOTTOM_CENTER, LINE_BOTTOM_LEFT, LINE_BOTTOM_RIGHT, LINE_CENTER_LEFT, LINE_CENTER_RIGHT, LINE_TOP_CENTER, LINE_TOP_LEFT, LINE_TOP_RIGHT};
 use crate::components::state::{BoardEvents, BoardState};
-use crate::engine::cell::CellValue;
+use sos_engine::cell::CellValue;
 use std::cmp::PartialEq;
 use std::rc::Rc;
 use yew::{html, Callback, Component, Context, ContextHandle, Html, MouseEvent, Properties};
@@ -14,12 +14,12 @@ pub enum Msg {
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct CellProps {
-    pub id: u16,
-    pub onselect: Callback<(u16,CellValue)>,
+    pub id: u32,
+    pub onselect: Callback<(u32,CellValue)>,
 }
 
 pub struct Cell {
-    id: u16,
+    id: u32,
     sys_lock: bool,
     state:Rc<BoardState>,
     _listener:ContextHandle<Rc<BoardState>>,
@@ -37,6 +37,53 @@ pub struct Cell {
 }
 
 
+impl Cell {
+    fn apply(&mut self, pattern: u8, val: Option<CellValue>) {
+        if let Some(val) = val {
+            self.value = val;
+            self.is_lock = true;
+        }
+
+        if pattern & LINE_TOP_LEFT == LINE_TOP_LEFT {
+            self.top_left = true;
+        }
+        if pattern & LINE_TOP_CENTER == LINE_TOP_CENTER {
+            self.top_center = true;
+        }
+        if pattern & LINE_TOP_RIGHT == LINE_TOP_RIGHT {
+            self.top_right = true;
+        }
+        if pattern & LINE_CENTER_LEFT == LINE_CENTER_LEFT {
+            self.center_left = true;
+        }
+        if pattern & LINE_CENTER_RIGHT == LINE_CENTER_RIGHT {
+            self.center_right = true;
+        }
+        if pattern & LINE_BOTTOM_LEFT == LINE_BOTTOM_LEFT {
+            self.bottom_left = true;
+        }
+        if pattern & LINE_BOTTOM_CENTER == LINE_BOTTOM_CENTER {
+            self.bottom_center = true;
+        }
+        if pattern & LINE_BOTTOM_RIGHT == LINE_BOTTOM_RIGHT {
+            self.bottom_right = true;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.is_lock = false;
+        self.value = CellValue::Empty;
+        self.top_left = false;
+        self.top_center = false;
+        self.top_right = false;
+        self.center_left = false;
+        self.center_right = false;
+        self.bottom_left = false;
+        self.bottom_center = false;
+        self.bottom_right = false;
+    }
+}
+
 impl Component for Cell {
     type Message = Msg;
     type Properties = CellProps;
@@ -85,36 +132,13 @@ impl Component for Cell {
                             return false;
                         }
                         let (pattern, val) = map[&self.id];
-                        if let Some(val) = val {
-                            self.value = val;
-                            self.is_lock = true;
-                        }
-
-                        if pattern & LINE_TOP_LEFT == LINE_TOP_LEFT {
-                            self.top_left = true;
-                        }
-                        if pattern & LINE_TOP_CENTER == LINE_TOP_CENTER {
-                            self.top_center = true;
-                        }
-                        if pattern & LINE_TOP_RIGHT == LINE_TOP_RIGHT {
-                            self.top_right = true;
-                        }
-                        if pattern & LINE_CENTER_LEFT == LINE_CENTER_LEFT {
-                            self.center_left = true;
-                        }
-                        if pattern & LINE_CENTER_RIGHT == LINE_CENTER_RIGHT {
-                            self.center_right = true;
-                        }
-                        if pattern & LINE_BOTTOM_LEFT == LINE_BOTTOM_LEFT {
-                            self.bottom_left = true;
-                        }
-                        if pattern & LINE_BOTTOM_CENTER == LINE_BOTTOM_CENTER {
-                            self.bottom_center = true;
-                        }
-                        if pattern & LINE_BOTTOM_RIGHT == LINE_BOTTOM_RIGHT {
-                            self.bottom_right = true;
+                        self.apply(pattern, val);
+                    },
+                    BoardEvents::Reset(map) => {
+                        self.clear();
+                        if let Some(&(pattern, val)) = map.get(&self.id) {
+                            self.apply(pattern, val);
                         }
-
                     },
                     _ => {}
                 }
@@ -1,7 +1,16 @@
+use sos_engine::solver::SolverLimits;
 
+// One color per seat, used for the scoreboard and the turn indicator.
+pub const PLAYER_COLORS: [&str; MAX_PLAYERS as usize] = ["#2E7D32", "#C62828", "#1565C0", "#EF6C00"];
 
-pub const PLAYER_TURN: u8 = 0;
-pub const COMPUTER_TURN: u8 = 1;
+// Endgames within these limits are solved to tell the player to move how the game ends with best
+// play. The solve runs on the UI thread without a clock, so the nodes keep it short.
+pub const FORECAST: SolverLimits = SolverLimits { cells: 10, nodes: 100_000 };
+
+pub const MIN_BOARD_SIZE: u16 = 3;
+pub const MAX_BOARD_SIZE: u16 = 30;
+pub const MIN_PLAYERS: u8 = 2;
+pub const MAX_PLAYERS: u8 = 4;
 
 pub const LINE_TOP_LEFT: u8 = 1;
 pub const LINE_TOP_CENTER: u8 = 2;
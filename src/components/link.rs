@@ -0,0 +1,35 @@
+use crate::components::settings::GameSettings;
+use sos_engine::game::Game;
+use sos_engine::share;
+use wasm_bindgen::JsValue;
+
+// The game is kept in the fragment, so it never reaches a server.
+const PREFIX: &str = "#game=";
+
+/// Puts the game into the address bar and returns the full link.
+pub fn share(game: &Game) -> String {
+    let window = gloo::utils::window();
+    let location = window.location();
+    let hash = format!("{}{}", PREFIX, share::encode(game));
+    let _ = location.set_hash(&hash);
+    let base = location.href().unwrap_or_default();
+    match base.split_once('#') {
+        Some((base, _)) => format!("{}{}", base, hash),
+        None => format!("{}{}", base, hash),
+    }
+}
+
+/// The game from the link the page was opened with, unless its board or players are outside what
+/// the settings offer. The fragment is removed again, so that a reload resumes the autosaved game
+/// instead of going back to the shared one.
+pub fn load() -> Option<Game> {
+    let window = gloo::utils::window();
+    let hash = window.location().hash().ok()?;
+    let code = hash.strip_prefix(PREFIX)?;
+    let game = share::decode(code).ok()
+        .filter(GameSettings::fits);
+    if let (Ok(history), Ok(path)) = (window.history(), window.location().pathname()) {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&path));
+    }
+    game
+}
@@ -1,4 +1,8 @@
 pub mod cell;
 pub mod board;
 pub mod state;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod settings;
+pub mod storage;
+pub mod record_file;
+pub mod link;
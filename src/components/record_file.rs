@@ -0,0 +1,103 @@
+use crate::components::constants::{MAX_PLAYERS, MIN_PLAYERS};
+use sos_engine::record::GameRecord;
+use gloo::file::callbacks::FileReader;
+use gloo::file::{Blob, File, ObjectUrl};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlAnchorElement, HtmlInputElement};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+/// Offers `text` to the browser as a file download.
+pub fn download(filename: &str, text: &str) {
+    let url = ObjectUrl::from(Blob::new_with_options(text, Some("text/plain")));
+    let Ok(element) = gloo::utils::document().create_element("a") else {
+        return;
+    };
+    let anchor: HtmlAnchorElement = element.unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+}
+
+/// Today's date in the `YYYY.MM.DD` form used by game records.
+pub fn today() -> String {
+    let date = js_sys::Date::new_0();
+    format!("{:04}.{:02}.{:02}", date.get_full_year(), date.get_month() + 1, date.get_date())
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct ImportProps {
+    pub onload: Callback<GameRecord>,
+}
+
+pub enum ImportMsg {
+    Choose(Event),
+    Loaded(Result<String, String>),
+}
+
+/// A button that reads a `.sos` game record from a file chosen by the user.
+pub struct Import {
+    // The read is cancelled when the reader is dropped.
+    reader: Option<FileReader>,
+    error: Option<String>,
+}
+
+impl Component for Import {
+    type Message = ImportMsg;
+    type Properties = ImportProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            reader: None,
+            error: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ImportMsg::Choose(event) => {
+                let input: HtmlInputElement = event.target_unchecked_into();
+                let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                    return false;
+                };
+                // Let the same file be chosen again after fixing it.
+                input.set_value("");
+                let link = ctx.link().clone();
+                self.reader = Some(gloo::file::callbacks::read_as_text(&File::from(file), move |result| {
+                    link.send_message(ImportMsg::Loaded(result.map_err(|e| e.to_string())));
+                }));
+                false
+            }
+            ImportMsg::Loaded(result) => {
+                self.reader = None;
+                let record = result.and_then(|text| GameRecord::parse(&text).map_err(|e| e.to_string()));
+                match record {
+                    Ok(record) if (MIN_PLAYERS as usize..=MAX_PLAYERS as usize).contains(&record.names.len()) => {
+                        self.error = None;
+                        ctx.props().onload.emit(record);
+                    }
+                    Ok(_) => {
+                        self.error = Some(format!("Only games with {} to {} players can be shown", MIN_PLAYERS, MAX_PLAYERS));
+                    }
+                    Err(error) => {
+                        self.error = Some(format!("Cannot import this game: {}", error));
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="import center">
+                <label class="control">
+                    {"Import game"}
+                    <input type="file" accept=".sos" style="display:none;" onchange={ctx.link().callback(ImportMsg::Choose)}/>
+                </label>
+                if let Some(error) = &self.error {
+                    <p class="lose-bar">{error.clone()}</p>
+                }
+            </div>
+        }
+    }
+}
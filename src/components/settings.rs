@@ -0,0 +1,240 @@
+use crate::components::constants::{MAX_BOARD_SIZE, MAX_PLAYERS, MIN_BOARD_SIZE, MIN_PLAYERS, PLAYER_COLORS};
+use sos_engine::difficulty::Difficulty;
+use sos_engine::game::Game;
+use sos_engine::rules::{GameMode, Rules};
+use web_sys::HtmlInputElement;
+use serde::{Deserialize, Serialize};
+use yew::{html, Callback, Component, Context, Event, Html, Properties, TargetCast};
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PlayerKind {
+    Human,
+    Computer,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerSettings {
+    pub name: String,
+    pub kind: PlayerKind,
+}
+
+impl PlayerSettings {
+    fn new(player: usize, kind: PlayerKind) -> Self {
+        let name = match kind {
+            PlayerKind::Human => format!("Player {}", player + 1),
+            PlayerKind::Computer => format!("Computer {}", player + 1),
+        };
+        Self { name, kind }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct GameSettings {
+    pub row: u16,
+    pub col: u16,
+    /// One entry per seat, in turn order.
+    pub players: Vec<PlayerSettings>,
+    pub difficulty: Difficulty,
+    pub rules: Rules,
+    /// Seeds the computer players, a new one is drawn for every game.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl GameSettings {
+    /// Settings for continuing an existing game with people in every seat.
+    pub fn hotseat(game: &Game, names: Vec<String>) -> Self {
+        Self {
+            row: game.get_rows(),
+            col: game.get_cols(),
+            players: names.into_iter()
+                .map(|name| PlayerSettings { name, kind: PlayerKind::Human })
+                .collect(),
+            rules: game.get_rules(),
+            ..GameSettings::default()
+        }
+    }
+
+    /// Whether the board and the players of `game` are within the range the settings offer.
+    pub fn fits(game: &Game) -> bool {
+        let sizes = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+        sizes.contains(&game.get_rows())
+            && sizes.contains(&game.get_cols())
+            && (MIN_PLAYERS..=MAX_PLAYERS).contains(&game.get_num_of_players())
+    }
+
+    pub fn has_computer(&self) -> bool {
+        self.players.iter().any(|player| player.kind == PlayerKind::Computer)
+    }
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            row: 10,
+            col: 10,
+            players: vec![
+                PlayerSettings::new(0, PlayerKind::Human),
+                PlayerSettings { name: "Computer".to_string(), kind: PlayerKind::Computer },
+            ],
+            difficulty: Difficulty::default(),
+            rules: Rules::classic(),
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct SettingsProps {
+    pub initial: GameSettings,
+    pub onstart: Callback<GameSettings>,
+}
+
+pub enum SettingsMsg {
+    SetRow(String),
+    SetCol(String),
+    SetPlayers(u8),
+    SetName(usize, String),
+    SetKind(usize, PlayerKind),
+    SetDifficulty(Difficulty),
+    SetMode(GameMode),
+    ToggleExtraTurn,
+    Start,
+}
+
+pub struct Settings {
+    settings: GameSettings,
+}
+
+impl Component for Settings {
+    type Message = SettingsMsg;
+    type Properties = SettingsProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            settings: ctx.props().initial.clone(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            SettingsMsg::SetRow(value) => {
+                self.settings.row = Settings::parse_size(&value, self.settings.row);
+            }
+            SettingsMsg::SetCol(value) => {
+                self.settings.col = Settings::parse_size(&value, self.settings.col);
+            }
+            SettingsMsg::SetPlayers(players) => {
+                let players = players as usize;
+                self.settings.players.truncate(players);
+                while self.settings.players.len() < players {
+                    let player = self.settings.players.len();
+                    self.settings.players.push(PlayerSettings::new(player, PlayerKind::Human));
+                }
+            }
+            SettingsMsg::SetName(player, name) => {
+                let name = name.trim();
+                self.settings.players[player].name = if name.is_empty() {
+                    PlayerSettings::new(player, self.settings.players[player].kind).name
+                } else {
+                    name.to_string()
+                };
+            }
+            SettingsMsg::SetKind(player, kind) => {
+                self.settings.players[player].kind = kind;
+            }
+            SettingsMsg::SetDifficulty(difficulty) => {
+                self.settings.difficulty = difficulty;
+            }
+            SettingsMsg::SetMode(mode) => {
+                self.settings.rules.mode = mode;
+            }
+            SettingsMsg::ToggleExtraTurn => {
+                self.settings.rules.extra_turn_on_sos = !self.settings.rules.extra_turn_on_sos;
+            }
+            SettingsMsg::Start => {
+                let seed = (js_sys::Math::random() * u32::MAX as f64) as u64;
+                ctx.props().onstart.emit(GameSettings { seed, ..self.settings.clone() });
+                return false;
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let settings = &self.settings;
+        let option = |selected: bool, label: &str, onclick: Callback<yew::MouseEvent>| {
+            let class = if selected { "control selected" } else { "control" };
+            html! { <button class={class} onclick={onclick}>{label.to_string()}</button> }
+        };
+
+        let players = (MIN_PLAYERS..=MAX_PLAYERS).map(|players| {
+            option(settings.players.len() == players as usize, &players.to_string(), link.callback(move |_| SettingsMsg::SetPlayers(players)))
+        });
+        let seats = settings.players.iter().enumerate().map(|(player, seat)| {
+            let kinds = [(PlayerKind::Human, "Human"), (PlayerKind::Computer, "Computer")].map(|(kind, label)| {
+                option(seat.kind == kind, label, link.callback(move |_| SettingsMsg::SetKind(player, kind)))
+            });
+            let on_name = link.callback(move |e: Event| SettingsMsg::SetName(player, e.target_unchecked_into::<HtmlInputElement>().value()));
+            html! {
+                <div class="setting">
+                    <span class="swatch" style={format!("color:{};", PLAYER_COLORS[player])}>{"\u{25CF}"}</span>
+                    <input class="name" type="text" value={seat.name.clone()} onchange={on_name}/>
+                    {for kinds}
+                </div>
+            }
+        });
+        let levels = Difficulty::ALL.map(|level| {
+            option(settings.difficulty == level, level.name(), link.callback(move |_| SettingsMsg::SetDifficulty(level)))
+        });
+        let modes = [(GameMode::Scoring, "Most SOS"), (GameMode::Simple, "First SOS")].map(|(mode, label)| {
+            option(settings.rules.mode == mode, label, link.callback(move |_| SettingsMsg::SetMode(mode)))
+        });
+        let extra_turn = option(settings.rules.extra_turn_on_sos, "Extra turn on SOS", link.callback(|_| SettingsMsg::ToggleExtraTurn));
+
+        let on_row = link.callback(|e: Event| SettingsMsg::SetRow(e.target_unchecked_into::<HtmlInputElement>().value()));
+        let on_col = link.callback(|e: Event| SettingsMsg::SetCol(e.target_unchecked_into::<HtmlInputElement>().value()));
+
+        html! {
+            <div class="settings center">
+                <div class="setting">
+                    <span class="setting-label">{"Board"}</span>
+                    <input class="size" type="number" min={MIN_BOARD_SIZE.to_string()} max={MAX_BOARD_SIZE.to_string()}
+                        value={settings.row.to_string()} onchange={on_row}/>
+                    {" x "}
+                    <input class="size" type="number" min={MIN_BOARD_SIZE.to_string()} max={MAX_BOARD_SIZE.to_string()}
+                        value={settings.col.to_string()} onchange={on_col}/>
+                </div>
+                <div class="setting">
+                    <span class="setting-label">{"Players"}</span>
+                    {for players}
+                </div>
+                {for seats}
+                if settings.has_computer() {
+                    <div class="setting">
+                        <span class="setting-label">{"Difficulty"}</span>
+                        {for levels}
+                    </div>
+                }
+                <div class="setting">
+                    <span class="setting-label">{"Winner"}</span>
+                    {for modes}
+                    {extra_turn}
+                </div>
+                <div class="controls center">
+                    <button class="control" onclick={link.callback(|_| SettingsMsg::Start)}>{"Start game"}</button>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Settings {
+    fn parse_size(value: &str, current: u16) -> u16 {
+        value.trim()
+            .parse::<u16>()
+            .map(|size| size.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE))
+            .unwrap_or(current)
+    }
+}
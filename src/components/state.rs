@@ -1,18 +1,20 @@
 use std::collections::HashMap;
 use yew::Callback;
-use crate::engine::cell::CellValue;
+use sos_engine::cell::CellValue;
 
 #[derive(Clone, PartialEq)]
 pub enum BoardEvents {
     Idle,
     Lock,
     Unlock,
-    Update(HashMap<u16, (u8, Option<CellValue>)>),
+    Update(HashMap<u32, (u8, Option<CellValue>)>),
+    /// Clears every cell and redraws the whole board from the given values and lines.
+    Reset(HashMap<u32, (u8, Option<CellValue>)>),
 }
 #[derive(Clone, PartialEq)]
 pub struct LockingState {
-    pub event: Callback<(u16, CellValue)>,
-    pub last_locked_id: Option<u16>,
+    pub event: Callback<(u32, CellValue)>,
+    pub last_locked_id: Option<u32>,
     pub last_locked_value: Option<CellValue>,
 }
 
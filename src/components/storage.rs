@@ -0,0 +1,31 @@
+use crate::components::settings::GameSettings;
+use sos_engine::game::Game;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+const STORAGE_KEY: &str = "sos-game.saved";
+
+/// An unfinished game together with the settings it was started with.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub settings: GameSettings,
+    pub game: Game,
+}
+
+pub fn save(settings: &GameSettings, game: &Game) {
+    let session = SavedSession {
+        settings: settings.clone(),
+        game: game.clone(),
+    };
+    // Running out of quota or a disabled storage only costs the autosave.
+    let _ = LocalStorage::set(STORAGE_KEY, session);
+}
+
+/// The saved game, if there is one that still loads with this version of the engine.
+pub fn load() -> Option<SavedSession> {
+    LocalStorage::get(STORAGE_KEY).ok()
+}
+
+pub fn clear() {
+    LocalStorage::delete(STORAGE_KEY);
+}
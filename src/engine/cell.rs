@@ -1,7 +0,0 @@
-
-#[derive(PartialEq, Clone, Copy, Debug)]
-pub enum CellValue {
-    Empty,
-    S,
-    O
-}
@@ -1,3 +0,0 @@
-pub mod game;
-pub mod cell;
-mod bot;
\ No newline at end of file
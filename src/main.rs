@@ -1,14 +1,79 @@
 mod components;
-mod engine;
 
-use yew::{function_component, html, Html};
+use sos_engine::record::GameRecord;
+use yew::{function_component, html, use_state, Callback, Html};
 use crate::components::board::Board;
+use crate::components::record_file::Import;
+use crate::components::link;
+use crate::components::settings::{GameSettings, Settings};
+use crate::components::storage;
 
 #[function_component]
 fn App() -> Html {
-    let row = 10;
-    let column = 10;
-    let width = 50*column;
+    // A game opened from a shared link starts right away.
+    let shared = use_state(link::load);
+    let settings = use_state(|| {
+        shared.as_ref()
+            .map(|game| GameSettings::hotseat(game, default_names(game.get_num_of_players())))
+            .unwrap_or_default()
+    });
+    let playing = use_state(|| shared.is_some());
+    let game = use_state(|| (*shared).clone());
+    // A game left unfinished in an earlier visit, offered once when the page loads.
+    let saved = use_state(storage::load);
+
+    let onstart = {
+        let settings = settings.clone();
+        let playing = playing.clone();
+        let game = game.clone();
+        Callback::from(move |chosen: GameSettings| {
+            settings.set(chosen);
+            game.set(None);
+            playing.set(true);
+        })
+    };
+    let onresume = {
+        let settings = settings.clone();
+        let playing = playing.clone();
+        let game = game.clone();
+        let saved = saved.clone();
+        Callback::from(move |_| {
+            if let Some(session) = (*saved).clone() {
+                settings.set(session.settings);
+                game.set(Some(session.game));
+                playing.set(true);
+            }
+            saved.set(None);
+        })
+    };
+    // Imported games are continued by people in every seat.
+    let onimport = {
+        let settings = settings.clone();
+        let playing = playing.clone();
+        let game = game.clone();
+        Callback::from(move |record: GameRecord| {
+            let Some(imported) = record.to_game().ok().filter(GameSettings::fits) else {
+                return;
+            };
+            settings.set(GameSettings::hotseat(&imported, record.names));
+            game.set(Some(imported));
+            playing.set(true);
+        })
+    };
+    let ondiscard = {
+        let saved = saved.clone();
+        Callback::from(move |_| {
+            storage::clear();
+            saved.set(None);
+        })
+    };
+    let onnewgame = {
+        let playing = playing.clone();
+        Callback::from(move |_| playing.set(false))
+    };
+
+    let column = settings.col;
+    let width = (50*column).max(500);
 
     let width_style = format!("width:{}px;", width+column-1);
     html! {
@@ -16,13 +81,30 @@ fn App() -> Html {
             <div class="container">
                 <div class="center"  style={width_style}>
                     <h1 class="center header">{"SOS Game"}</h1>
-                    <Board row={row} col={column}/>
+                    if *playing {
+                        <Board settings={(*settings).clone()} game={(*game).clone()}/>
+                        <div class="controls center">
+                            <button class="control" onclick={onnewgame}>{"New game"}</button>
+                        </div>
+                    } else if saved.is_some() {
+                        <p class="turn">{"You have an unfinished game."}</p>
+                        <div class="controls center">
+                            <button class="control" onclick={onresume}>{"Resume game"}</button>
+                            <button class="control" onclick={ondiscard}>{"New game"}</button>
+                        </div>
+                    } else {
+                        <Settings initial={(*settings).clone()} onstart={onstart}/>
+                        <Import onload={onimport}/>
+                    }
                 </div>
             </div>
         </>
     }
 }
 
+fn default_names(players: u8) -> Vec<String> {
+    (1..=players).map(|player| format!("Player {}", player)).collect()
+}
 
 fn main() {
 